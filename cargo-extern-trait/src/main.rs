@@ -0,0 +1,100 @@
+//! `cargo extern-trait`: lists the symbols generated by `#[extern_trait]` in ELF objects,
+//! `.rlib`/`.a` archives and executables, and reports missing, duplicate and mismatched
+//! implementations.
+
+mod report;
+mod symbol;
+
+use std::{path::PathBuf, process::ExitCode};
+
+use object::{FileKind, Object, ObjectSymbol, read::archive::ArchiveFile};
+
+use crate::{report::Report, symbol::Symbol};
+
+const USAGE: &str = "\
+Usage: cargo extern-trait <FILE>...
+
+Lists the symbols expected and provided for each #[extern_trait] in the given ELF objects,
+.rlib/.a archives and executables, and reports missing, duplicate and mismatched
+implementations.";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args_os().skip(1).peekable();
+    // Skip the subcommand name when invoked as `cargo extern-trait`.
+    if args.peek().is_some_and(|arg| arg == "extern-trait") {
+        args.next();
+    }
+    let paths = args.map(PathBuf::from).collect::<Vec<_>>();
+    if paths.is_empty() || paths.iter().any(|p| p == "-h" || p == "--help") {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut report = Report::default();
+    for path in &paths {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("error: failed to read {}: {e}", path.display());
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(e) = scan(&mut report, &path.display().to_string(), &data) {
+            eprintln!("error: failed to parse {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    print!("{report}");
+    if report.has_problems() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Adds the symbols of an object file or of every object file in an archive.
+fn scan(report: &mut Report, location: &str, data: &[u8]) -> object::Result<()> {
+    if FileKind::parse(data)? != FileKind::Archive {
+        return scan_object(report, location, data);
+    }
+
+    let archive = ArchiveFile::parse(data)?;
+    for member in archive.members() {
+        let member = member?;
+        let data = member.data(data)?;
+        // Archives also contain other members, such as the metadata of rlibs.
+        if !matches!(FileKind::parse(data), Ok(FileKind::Elf32 | FileKind::Elf64)) {
+            continue;
+        }
+        let name = String::from_utf8_lossy(member.name());
+        scan_object(report, &format!("{location}({name})"), data)?;
+    }
+    Ok(())
+}
+
+fn scan_object(report: &mut Report, location: &str, data: &[u8]) -> object::Result<()> {
+    let file = object::File::parse(data)?;
+    let mut symbols = file.symbols().peekable();
+    // Stripped executables only have dynamic symbols left.
+    let symbols = if symbols.peek().is_some() {
+        symbols.collect::<Vec<_>>()
+    } else {
+        file.dynamic_symbols().collect()
+    };
+
+    for symbol in symbols {
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        let Some(decoded) = Symbol::decode(name) else {
+            continue;
+        };
+        if symbol.is_undefined() {
+            report.expect(decoded, location);
+        } else {
+            report.provide(decoded, location);
+        }
+    }
+    Ok(())
+}
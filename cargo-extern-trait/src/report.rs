@@ -0,0 +1,187 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::symbol::Symbol;
+
+/// Identifies a trait across versions of its declaring crate.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct TraitKey {
+    trait_name: String,
+    crate_name: String,
+    package: String,
+    disambiguator: String,
+}
+
+/// Identifies a symbol of a trait.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ItemKey {
+    item: String,
+    version: String,
+    hash: Option<String>,
+}
+
+impl fmt::Display for ItemKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} @ {}", self.item, self.version)?;
+        if let Some(hash) = &self.hash {
+            write!(f, " [{hash}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Symbols of a trait and the objects referencing or defining them.
+#[derive(Default)]
+struct Entry {
+    expected: BTreeMap<ItemKey, BTreeSet<String>>,
+    provided: BTreeMap<ItemKey, BTreeSet<String>>,
+}
+
+impl Entry {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for key in self.expected.keys() {
+            if self.provided.contains_key(key) {
+                continue;
+            }
+            let others = self
+                .provided
+                .keys()
+                .filter(|other| other.item == key.item)
+                .collect::<Vec<_>>();
+            if let Some(other) = others.iter().find(|other| other.version == key.version) {
+                problems.push(format!(
+                    "signature mismatch: expected `{key}`, but found `{other}`"
+                ));
+            } else if !others.is_empty() {
+                let versions = others
+                    .iter()
+                    .map(|other| other.version.as_str())
+                    .collect::<BTreeSet<_>>();
+                problems.push(format!(
+                    "version mismatch: expected `{key}`, but only found versions {}",
+                    versions.into_iter().collect::<Vec<_>>().join(", ")
+                ));
+            } else {
+                problems.push(format!("missing implementation: `{key}`"));
+            }
+        }
+
+        for (key, locations) in &self.provided {
+            if locations.len() > 1 {
+                problems.push(format!(
+                    "duplicate implementation: `{key}` is defined in {}",
+                    locations.iter().cloned().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Symbols found in all scanned files, grouped by trait.
+#[derive(Default)]
+pub struct Report {
+    traits: BTreeMap<TraitKey, Entry>,
+}
+
+impl Report {
+    /// Records a symbol referenced by the proxy type of a trait.
+    pub fn expect(&mut self, symbol: Symbol, location: &str) {
+        let (trait_, item) = split(symbol);
+        let entry = self.traits.entry(trait_).or_default();
+        entry
+            .expected
+            .entry(item)
+            .or_default()
+            .insert(location.to_string());
+    }
+
+    /// Records a symbol defined by an implementation of a trait.
+    pub fn provide(&mut self, symbol: Symbol, location: &str) {
+        let (trait_, item) = split(symbol);
+        let entry = self.traits.entry(trait_).or_default();
+        entry
+            .provided
+            .entry(item)
+            .or_default()
+            .insert(location.to_string());
+    }
+
+    pub fn has_problems(&self) -> bool {
+        self.traits
+            .values()
+            .any(|entry| !entry.problems().is_empty())
+    }
+}
+
+fn split(symbol: Symbol) -> (TraitKey, ItemKey) {
+    let Symbol {
+        package,
+        version,
+        crate_name,
+        disambiguator,
+        trait_name,
+        item,
+        hash,
+    } = symbol;
+    (
+        TraitKey {
+            trait_name,
+            crate_name,
+            package,
+            disambiguator,
+        },
+        ItemKey {
+            item,
+            version,
+            hash,
+        },
+    )
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.traits.is_empty() {
+            return writeln!(f, "no #[extern_trait] symbols found");
+        }
+
+        for (key, entry) in &self.traits {
+            // The crate name is left empty when the crate is identified with `crate_id`.
+            if key.crate_name.is_empty() {
+                writeln!(
+                    f,
+                    "trait `{}` from crate id `{}` ({})",
+                    key.trait_name, key.package, key.disambiguator
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "trait `{}` from crate `{}` (package `{}`, {})",
+                    key.trait_name, key.crate_name, key.package, key.disambiguator
+                )?;
+            }
+            for (title, items) in [("expected", &entry.expected), ("provided", &entry.provided)] {
+                if items.is_empty() {
+                    continue;
+                }
+                writeln!(f, "  {title}:")?;
+                for (item, locations) in items {
+                    writeln!(f, "    {item}")?;
+                    for location in locations {
+                        writeln!(f, "      in {location}")?;
+                    }
+                }
+            }
+            for problem in entry.problems() {
+                writeln!(f, "  error: {problem}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
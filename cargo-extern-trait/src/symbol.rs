@@ -0,0 +1,81 @@
+/// A symbol generated by `#[extern_trait]`, decoded from its name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Symbol {
+    pub package: String,
+    pub version: String,
+    pub crate_name: String,
+    pub disambiguator: String,
+    pub trait_name: String,
+    /// The method name, prefixed by the supertrait for forwarded supertraits, or an item
+    /// generated by `#[extern_trait]` itself such as `$drop`.
+    pub item: String,
+    /// The hash of the method signature, if any.
+    pub hash: Option<String>,
+}
+
+impl Symbol {
+    /// Decodes a symbol name of the form `__extern_trait_` followed by length-prefixed
+    /// components, as generated by `#[extern_trait]`.
+    pub fn decode(name: &str) -> Option<Self> {
+        let mut rest = name.strip_prefix("__extern_trait_")?.as_bytes();
+        let mut components = Vec::new();
+        while !rest.is_empty() {
+            let (component, tail) = component(rest)?;
+            components.push(component);
+            rest = tail;
+        }
+
+        let mut components = components.into_iter();
+        let mut next = || components.next();
+        let (package, version, crate_name, disambiguator, trait_name) =
+            (next()?, next()?, next()?, next()?, next()?);
+        let mut items = components.collect::<Vec<_>>();
+        let hash = match items.len() {
+            0 => return None,
+            1 => None,
+            _ => items.pop(),
+        };
+
+        Some(Symbol {
+            package,
+            version,
+            crate_name,
+            disambiguator,
+            trait_name,
+            item: items.join("::"),
+            hash,
+        })
+    }
+}
+
+/// Splits off one length-prefixed component and unescapes it.
+fn component(input: &[u8]) -> Option<(String, &[u8])> {
+    let digits = input.iter().take_while(|b| b.is_ascii_digit()).count();
+    let len = std::str::from_utf8(&input[..digits]).ok()?.parse().ok()?;
+    let mut rest = &input[digits..];
+    // Empty components and escaped components starting with a digit or `_` are separated
+    // from their length.
+    if let [b'_', tail @ ..] = rest {
+        rest = tail;
+    }
+    if rest.len() < len {
+        return None;
+    }
+    let (escaped, rest) = rest.split_at(len);
+
+    let mut bytes = Vec::with_capacity(len);
+    let mut iter = escaped.iter();
+    while let Some(&b) = iter.next() {
+        if b != b'_' {
+            bytes.push(b);
+        } else if let Some(b'_') = iter.as_slice().first() {
+            iter.next();
+            bytes.push(b'_');
+        } else {
+            let hex = iter.as_slice().get(..2)?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            iter.nth(1);
+        }
+    }
+    Some((String::from_utf8(bytes).ok()?, rest))
+}
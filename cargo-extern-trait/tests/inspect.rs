@@ -0,0 +1,60 @@
+use std::{path::PathBuf, process::Command};
+
+use extern_trait::extern_trait;
+
+#[extern_trait(WidgetProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Widget {
+    fn new(id: u32) -> Self;
+    fn id(&self) -> u32;
+}
+
+struct WidgetImpl(u32);
+
+#[extern_trait]
+unsafe impl Widget for WidgetImpl {
+    fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+fn inspect(files: &[PathBuf]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-extern-trait"))
+        .arg("extern-trait")
+        .args(files)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_inspect() {
+    assert_eq!(WidgetProxy::new(7).id(), 7);
+
+    let exe = std::env::current_exe().unwrap();
+    let (success, stdout) = inspect(std::slice::from_ref(&exe));
+    assert!(success, "{stdout}");
+    assert!(
+        stdout.contains("trait `Widget` from crate `inspect`"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("    id @ "), "{stdout}");
+    assert!(stdout.contains("    new @ "), "{stdout}");
+
+    let copy = std::env::temp_dir().join(format!("extern-trait-inspect-{}", std::process::id()));
+    std::fs::copy(&exe, &copy).unwrap();
+    let (success, stdout) = inspect(&[exe, copy.clone()]);
+    std::fs::remove_file(copy).unwrap();
+    assert!(!success);
+    assert!(
+        stdout.contains("error: duplicate implementation: `new @ "),
+        "{stdout}"
+    );
+}
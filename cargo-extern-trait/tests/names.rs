@@ -0,0 +1,57 @@
+use std::process::Command;
+
+use extern_trait::extern_trait;
+
+// Names that need escaping in every component of the symbols.
+#[extern_trait(
+    GrößeProxy,
+    crate_id = "my.crate-1_0",
+    abi_version = "1.0-rc",
+    namespace = "2d_x"
+)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Größe {
+    fn new(value: u32) -> Self;
+    fn r#type(&self) -> u32;
+    fn get_value(&self) -> u32;
+}
+
+struct GrößeImpl(u32);
+
+#[extern_trait]
+unsafe impl Größe for GrößeImpl {
+    fn new(value: u32) -> Self {
+        Self(value)
+    }
+
+    fn r#type(&self) -> u32 {
+        self.0 + 1
+    }
+
+    fn get_value(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn test_names() {
+    let proxy = GrößeProxy::new(7);
+    assert_eq!((proxy.r#type(), proxy.get_value()), (8, 7));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-extern-trait"))
+        .arg(std::env::current_exe().unwrap())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("trait `Größe` from crate id `my.crate-1_0` (2d_x)"),
+        "{stdout}"
+    );
+    for item in ["new", "type", "get_value", "$drop"] {
+        assert!(
+            stdout.contains(&format!("    {item} @ abi:1.0-rc")),
+            "{stdout}"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use syn::{
+    Attribute, Error, Fields, Generics, Ident, ItemStruct, LitStr, Path, Result, Token, Type,
+    Visibility,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    parse_quote,
+};
+
+pub struct Args {
+    pub proxy: Proxy,
+    pub namespace: Option<LitStr>,
+    pub abi_version: Option<AbiVersion>,
+    pub crate_id: Option<LitStr>,
+    pub symbol: Option<LitStr>,
+    pub fallback: Option<Type>,
+    pub fallback_feature: Option<LitStr>,
+    pub checked_abi: Option<Ident>,
+    pub weak: Option<Ident>,
+    pub niche: Option<Niche>,
+    pub krate: Option<Path>,
+}
+
+/// The version that symbols are tied to instead of the exact package version.
+pub enum AbiVersion {
+    /// An explicit version, bumped deliberately by the declaring crate.
+    Explicit(LitStr),
+    /// The semver-compatible part of the package version, e.g. `1` for `1.2.3`.
+    Semver(Ident),
+}
+
+impl Parse for AbiVersion {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(LitStr) {
+            return input.parse().map(AbiVersion::Explicit);
+        }
+        let ident: Ident = input.parse()?;
+        if ident != "semver" {
+            return Err(Error::new_spanned(
+                ident,
+                "expected a string literal or `semver`",
+            ));
+        }
+        Ok(AbiVersion::Semver(ident))
+    }
+}
+
+/// A niche of the implementor that the proxy type exposes to `Option`.
+pub enum Niche {
+    /// The first word of the implementor is never null.
+    NonNull,
+}
+
+impl Parse for Niche {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident: Ident = input.parse()?;
+        if ident != "nonnull" {
+            return Err(Error::new_spanned(ident, "expected `nonnull`"));
+        }
+        Ok(Niche::NonNull)
+    }
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let proxy = input.parse()?;
+        let mut namespace = None;
+        let mut abi_version = None;
+        let mut crate_id = None;
+        let mut symbol = None;
+        let mut fallback = None;
+        let mut fallback_feature = None;
+        let mut checked_abi = None;
+        let mut weak = None;
+        let mut niche = None;
+        let mut krate = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key = input.call(Ident::parse_any)?;
+            let flag = if key == "checked_abi" {
+                Some(&mut checked_abi)
+            } else if key == "weak" {
+                Some(&mut weak)
+            } else {
+                None
+            };
+            if let Some(flag) = flag {
+                if flag.replace(key.clone()).is_some() {
+                    return Err(Error::new_spanned(
+                        key,
+                        "duplicate #[extern_trait] argument",
+                    ));
+                }
+                continue;
+            }
+            input.parse::<Token![=]>()?;
+            let duplicate = if key == "namespace" {
+                namespace.replace(input.parse()?).is_some()
+            } else if key == "abi_version" {
+                abi_version.replace(input.parse()?).is_some()
+            } else if key == "crate_id" {
+                crate_id.replace(input.parse()?).is_some()
+            } else if key == "symbol" {
+                symbol.replace(input.parse()?).is_some()
+            } else if key == "fallback" {
+                fallback.replace(input.parse()?).is_some()
+            } else if key == "fallback_feature" {
+                fallback_feature.replace(input.parse()?).is_some()
+            } else if key == "niche" {
+                niche.replace(input.parse()?).is_some()
+            } else if key == "crate" {
+                krate.replace(input.call(Path::parse_mod_style)?).is_some()
+            } else {
+                return Err(Error::new_spanned(key, "unknown #[extern_trait] argument"));
+            };
+            if duplicate {
+                return Err(Error::new_spanned(
+                    key,
+                    "duplicate #[extern_trait] argument",
+                ));
+            }
+        }
+
+        if let (None, Some(feature)) = (&fallback, &fallback_feature) {
+            return Err(Error::new_spanned(
+                feature,
+                "fallback_feature requires a fallback for #[extern_trait]",
+            ));
+        }
+
+        if let Some(weak) = &weak {
+            if !cfg!(feature = "nightly") {
+                return Err(Error::new_spanned(
+                    weak,
+                    "`weak` requires the `nightly` feature of extern-trait",
+                ));
+            }
+            if fallback.is_some() {
+                return Err(Error::new_spanned(
+                    weak,
+                    "a trait with a fallback is always implemented and may not be `weak`",
+                ));
+            }
+        }
+
+        Ok(Args {
+            proxy,
+            namespace,
+            abi_version,
+            crate_id,
+            symbol,
+            fallback,
+            fallback_feature,
+            checked_abi,
+            weak,
+            niche,
+            krate,
+        })
+    }
+}
+
+/// Returns the path of the runtime crate, which may be overridden with `crate = path` by
+/// crates re-exporting it.
+pub fn runtime_path(krate: Option<Path>) -> Path {
+    krate.unwrap_or_else(|| parse_quote!(::extern_trait))
+}
+
+/// Removes `#[extern_trait(symbol = "...")]` from the attributes of a method and returns
+/// the symbol name.
+pub fn take_symbol(attrs: &mut Vec<Attribute>) -> Result<Option<LitStr>> {
+    let mut symbol = None;
+    let mut result = Ok(());
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("extern_trait") {
+            return true;
+        }
+        let parsed = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("symbol") {
+                return Err(meta.error("unknown #[extern_trait] argument"));
+            }
+            if symbol.replace(meta.value()?.parse()?).is_some() {
+                return Err(meta.error("duplicate #[extern_trait] argument"));
+            }
+            Ok(())
+        });
+        if let Err(e) = parsed {
+            match &mut result {
+                Ok(()) => result = Err(e),
+                Err(errors) => errors.combine(e),
+            }
+        }
+        false
+    });
+    result.map(|()| symbol)
+}
+
+pub struct Proxy {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub generics: Generics,
+}
+
+impl Parse for Proxy {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        let generics = input.parse()?;
+
+        Ok(Proxy {
+            attrs,
+            vis,
+            ident,
+            generics,
+        })
+    }
+}
+
+impl From<Proxy> for ItemStruct {
+    fn from(value: Proxy) -> Self {
+        let Proxy {
+            attrs,
+            vis,
+            ident,
+            generics,
+        } = value;
+
+        ItemStruct {
+            attrs,
+            vis,
+            struct_token: Default::default(),
+            ident,
+            generics,
+            fields: Fields::Unnamed(parse_quote!((*const (), *const ()))),
+            semi_token: None,
+        }
+    }
+}
@@ -0,0 +1,1269 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Error, Fields, FnArg, GenericArgument, GenericParam, Generics, Ident, ItemStruct, ItemTrait,
+    Lifetime, LitStr, PathArguments, PathSegment, Result, ReturnType, Signature, TraitItem,
+    TraitItemFn, Type, TypeParamBound, ext::IdentExt, parse_quote,
+};
+
+use crate::{
+    args::{self, Args, Niche},
+    imp, lifetime, symbol,
+    ty::{SelfKind, TypeExt},
+};
+
+pub fn expand(args: Args, mut input: ItemTrait) -> Result<TokenStream> {
+    let lifetimes = lifetime_params(&input.generics)?;
+    let proxy_lifetimes = lifetime_params(&args.proxy.generics)?;
+    if proxy_lifetimes != lifetimes {
+        return Err(Error::new_spanned(
+            &args.proxy.ident,
+            "the proxy type must have the same lifetime parameters as the trait",
+        ));
+    }
+    if let (Some(fallback), false) = (&args.fallback, lifetimes.is_empty()) {
+        return Err(Error::new_spanned(
+            fallback,
+            "#[extern_trait] with lifetime parameters may not have a fallback",
+        ));
+    }
+    let generics = input.generics.clone();
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let trait_name = &input.ident;
+    // With `checked_abi`, the requirements on implementors are checked at compile time.
+    if input.unsafety.is_none() && args.checked_abi.is_none() {
+        return Err(Error::new(
+            Span::call_site(),
+            "#[extern_trait] must be unsafe unless it uses `checked_abi`",
+        ));
+    }
+    let unsafety = &input.unsafety;
+
+    let mut proxy = ItemStruct::from(args.proxy);
+    let proxy_name = &proxy.ident.clone();
+    let proxy_ty: Box<Type> = parse_quote!(#proxy_name #ty_generics);
+    let trait_ty = quote!(#trait_name #ty_generics);
+    let mut impl_content = TokenStream::new();
+
+    let disambiguator = symbol::disambiguator(args.namespace.as_ref(), trait_name)?;
+    let [package, crate_name] = symbol::crate_id(args.crate_id.as_ref())?;
+    let version = symbol::version(args.abi_version.as_ref())?;
+
+    // Traits with the same name and disambiguator would share their symbols. The name of the
+    // exported macro is unique per crate, so such a collision fails with an error naming it.
+    let macro_name = format_ident!(
+        "__extern_trait_{}_{}_needs_a_unique_namespace",
+        trait_name,
+        disambiguator
+    );
+    let mut macro_content = TokenStream::new();
+
+    let symbol_prefix = match &args.symbol {
+        Some(symbol) => symbol::verbatim(symbol)?,
+        None => symbol::prefix(&[
+            &package,
+            &version,
+            &crate_name,
+            &disambiguator,
+            &trait_name.unraw().to_string(),
+        ]),
+    };
+    let mut export_names = HashSet::new();
+    let crate_name = if crate_name.is_empty() {
+        &package
+    } else {
+        &crate_name
+    };
+    // A custom prefix has to stay the same when the trait is renamed or moved, and may be
+    // implemented without `#[extern_trait]`, so no sentinels are generated for it.
+    let sentinels = args.symbol.is_none().then(|| {
+        [
+            symbol::sentinel("MISSING", trait_name, crate_name, &symbol_prefix),
+            symbol::sentinel("DUPLICATE", trait_name, crate_name, &symbol_prefix),
+        ]
+    });
+    let linkage = if args.weak.is_some() {
+        Linkage::Weak
+    } else {
+        Linkage::Strong {
+            sentinel: sentinels.as_ref().map(|[missing, _]| missing.as_str()),
+        }
+    };
+
+    // The shims in the exported macro are generic over the lifetimes of the trait, which are
+    // replaced by those of the impl. Other items get the implementor with `'static` lifetimes.
+    let shim_generics = (!lifetimes.is_empty()).then(|| quote!(<#(#lifetimes),*>));
+    let static_ty = if lifetimes.is_empty() {
+        quote!($ty)
+    } else {
+        quote!($static_ty)
+    };
+
+    let mut views = Views {
+        ref_name: format_ident!("{}Ref", proxy_name),
+        mut_name: format_ident!("{}Mut", proxy_name),
+        ref_content: TokenStream::new(),
+        mut_content: TokenStream::new(),
+        shared_imports: TokenStream::new(),
+    };
+
+    for t in &mut input.items {
+        let TraitItem::Fn(f) = t else {
+            impl_content.extend(
+                Error::new_spanned(t, "#[extern_trait] may only contain methods")
+                    .to_compile_error(),
+            );
+            continue;
+        };
+
+        let export_name = match method_export_name(&symbol_prefix, f, &mut export_names) {
+            Ok(export_name) => export_name,
+            Err(e) => {
+                impl_content.extend(e.to_compile_error());
+                continue;
+            }
+        };
+
+        // Borrowed proxies would need another lifetime besides those of the trait.
+        let borrowed = borrowed_receiver(&f.sig).filter(|_| lifetimes.is_empty());
+        // A symbol may only be imported once with weak linkage, so the borrowed proxies use
+        // the import of the proxy, which is moved into a function returning it.
+        let shared = borrowed.map(|_| format_ident!("__extern_trait_{}", f.sig.ident));
+        let proxy_impl = generate_proxy_impl(
+            proxy_name,
+            &proxy_ty,
+            &export_name,
+            &linkage,
+            &lifetimes,
+            &f.sig,
+            shared.as_ref(),
+        );
+        match proxy_impl {
+            Ok((i, shared_import)) => {
+                impl_content.extend(i);
+                views.shared_imports.extend(shared_import);
+                if let (Some(mutable), Some(shared)) = (borrowed, &shared) {
+                    let view_impl = generate_borrowed_impl(proxy_name, shared, f, mutable);
+                    if !mutable {
+                        views.ref_content.extend(view_impl.clone());
+                    }
+                    views.mut_content.extend(view_impl);
+                }
+                macro_content.extend(generate_macro_rules(
+                    None,
+                    &export_name,
+                    &f.sig,
+                    &shim_generics,
+                    &static_ty,
+                ));
+            }
+            Err(e) => {
+                impl_content.extend(e.to_compile_error());
+            }
+        }
+    }
+
+    let mut extra_impls = TokenStream::new();
+    let mut unpin = false;
+
+    for t in &input.supertraits {
+        if let TypeParamBound::Trait(t) = t
+            && t.path.leading_colon.is_none()
+            && t.path.segments.len() == 1
+        {
+            let PathSegment { ident, arguments } = &t.path.segments[0];
+            if ident == "Send" {
+                extra_impls.extend(quote! {
+                    unsafe impl #impl_generics Send for #proxy_ty {}
+                });
+            } else if ident == "Sync" {
+                extra_impls.extend(quote! {
+                    unsafe impl #impl_generics Sync for #proxy_ty {}
+                });
+            } else if ident == "Unpin" {
+                unpin = true;
+            } else if ident == "AsRef"
+                && let PathArguments::AngleBracketed(args) = arguments
+                && let Some(GenericArgument::Type(ty)) = args.args.first()
+            {
+                let sig = parse_quote!(fn as_ref(&self) -> &#ty);
+                let export_name = symbol::export_name(
+                    &symbol::item(&symbol_prefix, &format!("AsRef<{}>", ty.to_token_stream())),
+                    &sig,
+                );
+                let (impl_content, _) = generate_proxy_impl(
+                    proxy_name,
+                    &proxy_ty,
+                    &export_name,
+                    &linkage,
+                    &lifetimes,
+                    &sig,
+                    None,
+                )?;
+                extra_impls.extend(quote! {
+                    impl #impl_generics AsRef<#ty> for #proxy_ty {
+                        #impl_content
+                    }
+                });
+                macro_content.extend(generate_macro_rules(
+                    Some(quote!(AsRef<#ty>)),
+                    &export_name,
+                    &sig,
+                    &shim_generics,
+                    &static_ty,
+                ));
+            }
+            // TODO: support more traits
+        }
+    }
+
+    // The implementor may be `!Unpin`, so a pinned proxy must not be movable either.
+    if !unpin
+        && input
+            .items
+            .iter()
+            .any(|item| uses_self_kind(item, |kind| kind.is_pinned()))
+        && let Fields::Unnamed(fields) = &mut proxy.fields
+    {
+        fields
+            .unnamed
+            .push(parse_quote!(::core::marker::PhantomPinned));
+    }
+    let mut phantom_fields =
+        (proxy.fields.len() > 2).then(|| quote!(, ::core::marker::PhantomPinned));
+    if !lifetimes.is_empty()
+        && let Fields::Unnamed(fields) = &mut proxy.fields
+    {
+        fields
+            .unnamed
+            .push(parse_quote!(::core::marker::PhantomData<(#(&#lifetimes (),)*)>));
+        phantom_fields
+            .get_or_insert_default()
+            .extend(quote!(, ::core::marker::PhantomData));
+    }
+    // `Option<Proxy>` can then use the null value of the first word.
+    let (first_part, from_first_part) = match &args.niche {
+        Some(Niche::NonNull) => {
+            if let Fields::Unnamed(fields) = &mut proxy.fields {
+                fields.unnamed[0].ty = parse_quote!(::core::ptr::NonNull<()>);
+            }
+            (
+                quote!(this.0.as_ptr().cast_const()),
+                quote!(unsafe { ::core::ptr::NonNull::new_unchecked(parts[0].cast_mut()) }),
+            )
+        }
+        None => (quote!(this.0), quote!(parts[0])),
+    };
+
+    let drop_name = symbol::item(&symbol_prefix, "$drop");
+    let reflect_name = symbol::item(&symbol_prefix, "$reflect");
+    let missing = format!("no implementation of `{trait_name}` is linked");
+    let import_drop = import(
+        &drop_name,
+        &linkage,
+        &lifetimes,
+        &format_ident!("drop"),
+        &[parse_quote!(*mut #proxy_ty)],
+        &ReturnType::Default,
+        &missing,
+    );
+    let import_reflect = import(
+        &reflect_name,
+        &linkage,
+        &lifetimes,
+        &format_ident!("reflect"),
+        std::slice::from_ref(&proxy_ty),
+        &parse_quote!(-> #proxy_ty),
+        &missing,
+    );
+    let type_id_name = symbol::item(&symbol_prefix, "$type_id");
+    let type_name_name = symbol::item(&symbol_prefix, "$type_name");
+    let import_type_id = import(
+        &type_id_name,
+        &linkage,
+        &[],
+        &format_ident!("type_id"),
+        &[],
+        &parse_quote!(-> ::core::any::TypeId),
+        &missing,
+    );
+    let import_type_name = import(
+        &type_name_name,
+        &linkage,
+        &[],
+        &format_ident!("type_name"),
+        &[],
+        &parse_quote!(-> &'static str),
+        &missing,
+    );
+    let layout_name = symbol::item(&symbol_prefix, "$layout");
+    let import_layout = import(
+        &layout_name,
+        &linkage,
+        &[],
+        &format_ident!("layout"),
+        &[],
+        &parse_quote!(-> (::core::alloc::Layout, bool)),
+        &missing,
+    );
+    let check_exact_layout = input
+        .items
+        .iter()
+        .any(|item| uses_self_kind(item, |kind| kind.needs_exact_layout()))
+        .then(|| {
+            quote! {
+                if layout != proxy {
+                    return ::core::result::Result::Err(
+                        "the implementation type must have the same size and alignment as the proxy type",
+                    );
+                }
+            }
+        });
+    let is_implemented = matches!(linkage, Linkage::Weak).then(|| {
+        quote! {
+            /// Returns whether an implementation of the trait is linked.
+            pub fn is_implemented() -> bool {
+                unsafe extern "Rust" {
+                    #[linkage = "extern_weak"]
+                    #[link_name = #drop_name]
+                    static DROP: ::core::option::Option<extern "Rust" fn(*mut #proxy_name)>;
+                }
+                unsafe { DROP }.is_some()
+            }
+        }
+    });
+    let runtime = args::runtime_path(args.krate);
+    // The rules are expanded in the implementing crate, where `crate` means another crate.
+    let krate = match runtime.segments.first() {
+        Some(first) if runtime.leading_colon.is_none() && first.ident == "crate" => {
+            let rest = runtime.segments.iter().skip(1);
+            quote!($crate #(::#rest)*)
+        }
+        _ => runtime.to_token_stream(),
+    };
+    let check_abi = args.checked_abi.as_ref().map(|_| {
+        quote! {
+            fn register_safe<T: #krate::RegisterSafe>() {}
+            const _: fn() = register_safe::<#static_ty>;
+        }
+    });
+    let check_niche = args.niche.as_ref().map(|Niche::NonNull| {
+        quote! {
+            fn non_null_first<T: #krate::NonNullFirst>() {}
+            const _: fn() = non_null_first::<#static_ty>;
+        }
+    });
+    // The proxy type may be private to the declaring crate, so implementations are marked
+    // with a hash of the symbol prefix instead, which is unique to the trait as well.
+    let key = Literal::u64_suffixed(symbol::hash(&symbol_prefix));
+    let define_sentinels = sentinels.as_ref().map(|[missing, duplicate]| {
+        quote! {
+            #[doc(hidden)]
+            #[unsafe(export_name = #duplicate)]
+            static DUPLICATE: u8 = 0;
+
+            #[doc(hidden)]
+            #[unsafe(export_name = #missing)]
+            static MISSING: u8 = 0;
+        }
+    });
+    let pattern = if lifetimes.is_empty() {
+        quote!($trait:path: $ty:ty)
+    } else {
+        let metavars = lifetimes.iter().map(lifetime::metavar);
+        quote!($trait:path: $ty:ty, #($#metavars:lifetime),*; $static_ty:ty)
+    };
+    let rules = quote! {
+        (#pattern) => {
+            const _: () = {
+                #check_abi
+
+                #check_niche
+
+                unsafe impl #shim_generics #krate::ExternImplOf<#key> for $ty {}
+
+                #define_sentinels
+
+                #macro_content
+
+                #[doc(hidden)]
+                #[unsafe(export_name = #drop_name)]
+                extern "Rust" fn drop #shim_generics(this: &mut $ty) {
+                    unsafe { ::core::ptr::drop_in_place(this) };
+                }
+
+                // `MaybeUninit` has the same ABI as the implementor, and may be passed any bits
+                // by `verify_abi`.
+                #[doc(hidden)]
+                #[unsafe(export_name = #reflect_name)]
+                extern "Rust" fn reflect #shim_generics(
+                    this: ::core::mem::MaybeUninit<$ty>,
+                ) -> ::core::mem::MaybeUninit<$ty> {
+                    this
+                }
+
+                #[doc(hidden)]
+                #[unsafe(export_name = #layout_name)]
+                extern "Rust" fn layout #shim_generics() -> (::core::alloc::Layout, bool) {
+                    (
+                        ::core::alloc::Layout::new::<$ty>(),
+                        ::core::mem::needs_drop::<$ty>(),
+                    )
+                }
+
+                #[doc(hidden)]
+                #[unsafe(export_name = #type_id_name)]
+                extern "Rust" fn type_id() -> ::core::any::TypeId {
+                    ::core::any::TypeId::of::<#static_ty>()
+                }
+
+                #[doc(hidden)]
+                #[unsafe(export_name = #type_name_name)]
+                extern "Rust" fn type_name() -> &'static str {
+                    ::core::any::type_name::<#static_ty>()
+                }
+            };
+        };
+    };
+    let rules = lifetime::into_metavars(rules, &lifetimes);
+    let exported_macro = match &args.fallback {
+        None => quote! {
+            #[doc(hidden)]
+            #[macro_export]
+            macro_rules! #macro_name {
+                #rules
+            }
+        },
+        Some(fallback) => {
+            let feature = args
+                .fallback_feature
+                .as_ref()
+                .map_or_else(|| "fallback".to_string(), LitStr::value);
+            let message = format!(
+                "`{trait_name}` already has the fallback implementation `{}`, \
+                 disable the `{feature}` feature of the crate declaring it to implement it here",
+                fallback.to_token_stream()
+            );
+            let assert_size = imp::assert_size(fallback);
+            quote! {
+                #[cfg(not(feature = #feature))]
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #macro_name {
+                    #rules
+                }
+
+                #[cfg(feature = #feature)]
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #macro_name {
+                    ($trait:path: $ty:ty) => {
+                        ::core::compile_error!(#message);
+                    };
+                }
+
+                #[cfg(feature = #feature)]
+                const _: () = {
+                    #assert_size
+
+                    macro_rules! fallback {
+                        #rules
+                    }
+                    fallback!(#trait_name: #fallback);
+                };
+            }
+        }
+    };
+    let generic_doc = format!(
+        "`T` must implement [`{}`] via `#[extern_trait]`.",
+        trait_name
+    );
+    let checked_doc = format!(
+        "`T` has to implement [`{}`] via `#[extern_trait]`, which is checked at compile time.",
+        trait_name
+    );
+
+    let Views {
+        ref_name,
+        mut_name,
+        ref_content,
+        mut_content,
+        shared_imports,
+        ..
+    } = views;
+    let vis = &proxy.vis;
+    let ref_doc = format!(
+        "A shared borrow of an implementation of [`{trait_name}`], which forwards the `&self` \
+         methods like [`{proxy_name}`]."
+    );
+    let mut_doc = format!(
+        "A mutable borrow of an implementation of [`{trait_name}`], which forwards the `&self` \
+         and `&mut self` methods like [`{proxy_name}`]."
+    );
+
+    let views = lifetimes.is_empty().then(|| {
+        quote! {
+            #[doc = #ref_doc]
+            #[derive(Clone, Copy)]
+            #vis struct #ref_name<'a>(
+                ::core::ptr::NonNull<()>,
+                ::core::marker::PhantomData<&'a #proxy_name>,
+            );
+
+            unsafe impl<'a> Send for #ref_name<'a> where &'a #proxy_name: Send {}
+            unsafe impl<'a> Sync for #ref_name<'a> where &'a #proxy_name: Sync {}
+
+            impl<'a> #ref_name<'a> {
+                /// Borrows the implementation type.
+                ///
+                #[doc = #checked_doc]
+                pub fn wrap<T: #trait_ty + #runtime::ExternImplOf<#key>>(value: &'a T) -> Self {
+                    Self(::core::ptr::NonNull::from(value).cast(), ::core::marker::PhantomData)
+                }
+
+                #ref_content
+            }
+
+            impl<'a> From<&'a #proxy_name> for #ref_name<'a> {
+                fn from(proxy: &'a #proxy_name) -> Self {
+                    Self(::core::ptr::NonNull::from(proxy).cast(), ::core::marker::PhantomData)
+                }
+            }
+
+            #[doc = #mut_doc]
+            #vis struct #mut_name<'a>(
+                ::core::ptr::NonNull<()>,
+                ::core::marker::PhantomData<&'a mut #proxy_name>,
+            );
+
+            unsafe impl<'a> Send for #mut_name<'a> where &'a mut #proxy_name: Send {}
+            unsafe impl<'a> Sync for #mut_name<'a> where &'a mut #proxy_name: Sync {}
+
+            impl<'a> #mut_name<'a> {
+                /// Mutably borrows the implementation type.
+                ///
+                #[doc = #checked_doc]
+                pub fn wrap<T: #trait_ty + #runtime::ExternImplOf<#key>>(value: &'a mut T) -> Self {
+                    Self(::core::ptr::NonNull::from(value).cast(), ::core::marker::PhantomData)
+                }
+
+                /// Reborrows it as a shared borrow.
+                pub fn as_proxy_ref(&self) -> #ref_name<'_> {
+                    #ref_name(self.0, ::core::marker::PhantomData)
+                }
+
+                #mut_content
+            }
+
+            impl<'a> From<&'a mut #proxy_name> for #mut_name<'a> {
+                fn from(proxy: &'a mut #proxy_name) -> Self {
+                    Self(::core::ptr::NonNull::from(proxy).cast(), ::core::marker::PhantomData)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        #proxy
+
+        #views
+
+        #unsafety impl #impl_generics #trait_ty for #proxy_ty {
+            #impl_content
+        }
+
+        #extra_impls
+
+        impl<#(#lifetimes,)* T: #trait_ty + #runtime::ExternImplOf<#key>> ::core::convert::From<T>
+            for #proxy_ty
+        {
+            fn from(value: T) -> Self {
+                Self::wrap(value)
+            }
+        }
+
+        impl #impl_generics Drop for #proxy_ty {
+            fn drop(&mut self) {
+                #import_drop
+                drop(self)
+            }
+        }
+
+        impl #impl_generics #proxy_ty {
+            #is_implemented
+
+            #shared_imports
+
+            unsafe fn reflect<T, R>() -> extern "Rust" fn(T) -> R {
+                #import_reflect
+                unsafe {
+                    ::core::mem::transmute::<_, extern "Rust" fn(T) -> R>(reflect as *const ())
+                }
+            }
+
+            /// Convert the proxy type from the implementation type.
+            /// # Safety
+            #[doc = #generic_doc]
+            pub unsafe fn from_impl<T: #trait_ty>(value: T) -> Self {
+                unsafe { Self::reflect::<T, Self>()(value) }
+            }
+
+            /// Convert the proxy type into the implementation type.
+            /// # Safety
+            #[doc = #generic_doc]
+            pub unsafe fn into_impl<T: #trait_ty>(self) -> T {
+                unsafe { Self::reflect::<Self, T>()(self) }
+            }
+
+            /// Returns a reference to the implementation type.
+            /// # Safety
+            #[doc = #generic_doc]
+            pub unsafe fn downcast_ref<T: #trait_ty>(&self) -> &T {
+                unsafe { &*(self as *const Self as *const T) }
+            }
+
+            /// Returns a mutable reference to the implementation type.
+            /// # Safety
+            #[doc = #generic_doc]
+            pub unsafe fn downcast_mut<T: #trait_ty>(&mut self) -> &mut T {
+                unsafe { &mut *(self as *mut Self as *mut T) }
+            }
+
+            /// Converts the proxy into the two words it consists of, without dropping it.
+            ///
+            /// The words may be passed through foreign code, e.g. as `void *` pointers, and
+            /// converted back with [`from_raw_parts`](Self::from_raw_parts).
+            pub fn into_raw_parts(self) -> [*const (); 2] {
+                let this = ::core::mem::ManuallyDrop::new(self);
+                [#first_part, this.1]
+            }
+
+            /// Converts the words returned by [`into_raw_parts`](Self::into_raw_parts) back
+            /// into the proxy.
+            /// # Safety
+            /// `parts` must have been returned by `into_raw_parts` of this proxy type, and may
+            /// only be converted back once.
+            pub unsafe fn from_raw_parts(parts: [*const (); 2]) -> Self {
+                Self(#from_first_part, parts[1] #phantom_fields)
+            }
+
+            /// Returns the [`TypeId`](::core::any::TypeId) of the linked implementation type.
+            pub fn implementor_type_id() -> ::core::any::TypeId {
+                #import_type_id
+                type_id()
+            }
+
+            /// Returns the name of the linked implementation type, for diagnostics only.
+            pub fn implementor_type_name() -> &'static str {
+                #import_type_name
+                type_name()
+            }
+
+            fn implementor_layout() -> (::core::alloc::Layout, bool) {
+                #import_layout
+                layout()
+            }
+
+            /// Returns the memory layout of the linked implementation type.
+            pub fn layout() -> ::core::alloc::Layout {
+                Self::implementor_layout().0
+            }
+
+            /// Returns whether the linked implementation type needs to be dropped.
+            pub fn needs_drop() -> bool {
+                Self::implementor_layout().1
+            }
+
+            /// Checks that the layout of the linked implementation type is compatible with
+            /// the proxy type.
+            ///
+            /// This is already checked when compiling the implementation, but may be repeated
+            /// at startup to validate what was actually linked.
+            pub fn self_check() -> ::core::result::Result<(), &'static str> {
+                let layout = Self::layout();
+                let proxy = ::core::alloc::Layout::new::<[*const (); 2]>();
+                if layout.size() > proxy.size() {
+                    return ::core::result::Result::Err(
+                        "the implementation type is larger than the proxy type",
+                    );
+                }
+                if layout.align() > proxy.align() {
+                    return ::core::result::Result::Err(
+                        "the implementation type is more strictly aligned than the proxy type",
+                    );
+                }
+                #check_exact_layout
+                ::core::result::Result::Ok(())
+            }
+
+            /// Checks whether the linked implementation type is passed and returned the same
+            /// way as the proxy type, e.g. not in floating point registers.
+            ///
+            /// Two bit patterns are passed through the implementation by value and compared
+            /// word by word with what comes back. Bytes of padding may be lost on the way, so
+            /// a word of the implementation type only has to keep one of its bytes. This is
+            /// best-effort: a mismatching calling convention may go unnoticed if the registers
+            /// happen to hold the expected values.
+            pub fn verify_abi() -> bool {
+                const WORD: usize = ::core::mem::size_of::<*const ()>();
+                let words = Self::layout().size().div_ceil(WORD);
+                let patterns = [0xa5, 0x5a].map(|byte| usize::MAX / 0xff * byte);
+                let returned = patterns.map(|pattern| {
+                    let parts = [::core::ptr::without_provenance(pattern); 2];
+                    let proxy = ::core::mem::ManuallyDrop::new(unsafe { Self::from_raw_parts(parts) });
+                    let reflect = unsafe {
+                        Self::reflect::<::core::mem::ManuallyDrop<Self>, ::core::mem::ManuallyDrop<Self>>()
+                    };
+                    ::core::mem::ManuallyDrop::into_inner(reflect(proxy)).into_raw_parts()
+                });
+                (0..words).all(|word| {
+                    (0..WORD).any(|byte| {
+                        let mask = 0xff << (byte * 8);
+                        patterns
+                            .iter()
+                            .zip(&returned)
+                            .all(|(pattern, returned)| returned[word].addr() & mask == pattern & mask)
+                    })
+                })
+            }
+
+            /// Returns whether `T` is the linked implementation type.
+            pub fn is<T: ?Sized + 'static>() -> bool {
+                Self::implementor_type_id() == ::core::any::TypeId::of::<T>()
+            }
+
+            /// Returns a reference to the implementation type, or `None` if `T` is not the
+            /// linked implementation type.
+            pub fn try_downcast_ref<T: #trait_ty + 'static>(&self) -> ::core::option::Option<&T> {
+                Self::is::<T>().then(|| unsafe { self.downcast_ref() })
+            }
+
+            /// Returns a mutable reference to the implementation type, or `None` if `T` is
+            /// not the linked implementation type.
+            pub fn try_downcast_mut<T: #trait_ty + 'static>(&mut self) -> ::core::option::Option<&mut T> {
+                if Self::is::<T>() {
+                    ::core::option::Option::Some(unsafe { self.downcast_mut() })
+                } else {
+                    ::core::option::Option::None
+                }
+            }
+
+            /// Convert the proxy type into the implementation type, or return it unchanged
+            /// if `T` is not the linked implementation type.
+            pub fn try_into_impl<T: #trait_ty + 'static>(self) -> ::core::result::Result<T, Self> {
+                if Self::is::<T>() {
+                    ::core::result::Result::Ok(unsafe { self.into_impl() })
+                } else {
+                    ::core::result::Result::Err(self)
+                }
+            }
+
+            /// Convert the proxy type from the implementation type.
+            ///
+            #[doc = #checked_doc]
+            pub fn wrap<T: #trait_ty + #runtime::ExternImplOf<#key>>(value: T) -> Self {
+                unsafe { Self::from_impl(value) }
+            }
+
+            /// Convert the proxy type into the implementation type.
+            ///
+            #[doc = #checked_doc]
+            pub fn into_inner<T: #trait_ty + #runtime::ExternImplOf<#key>>(self) -> T {
+                unsafe { self.into_impl() }
+            }
+
+            /// Returns a reference to the implementation type.
+            ///
+            #[doc = #checked_doc]
+            pub fn as_inner<T: #trait_ty + #runtime::ExternImplOf<#key>>(&self) -> &T {
+                unsafe { self.downcast_ref() }
+            }
+
+            /// Returns a mutable reference to the implementation type.
+            ///
+            #[doc = #checked_doc]
+            pub fn as_inner_mut<T: #trait_ty + #runtime::ExternImplOf<#key>>(&mut self) -> &mut T {
+                unsafe { self.downcast_mut() }
+            }
+        }
+
+        #exported_macro
+
+        #[doc(hidden)]
+        pub use #macro_name as #trait_name;
+    })
+}
+
+/// Returns the export name of a method, which may be overridden with
+/// `#[extern_trait(symbol = "...")]`.
+fn method_export_name(
+    prefix: &str,
+    f: &mut TraitItemFn,
+    export_names: &mut HashSet<String>,
+) -> Result<String> {
+    let Some(symbol) = args::take_symbol(&mut f.attrs)? else {
+        return Ok(symbol::export_name(prefix, &f.sig));
+    };
+    let export_name = symbol::verbatim(&symbol)?;
+    if !export_names.insert(export_name.clone()) {
+        return Err(Error::new_spanned(
+            symbol,
+            "duplicate symbol in #[extern_trait]",
+        ));
+    }
+    Ok(export_name)
+}
+
+/// Returns whether `item` is a method using a form of `Self` matching `predicate`.
+fn uses_self_kind(item: &TraitItem, predicate: fn(SelfKind) -> bool) -> bool {
+    let TraitItem::Fn(f) = item else {
+        return false;
+    };
+    let output = match &f.sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(&**ty),
+    };
+    f.sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Receiver(arg) => &*arg.ty,
+            FnArg::Typed(arg) => &*arg.ty,
+        })
+        .chain(output)
+        .any(|ty| ty.self_kind().is_some_and(predicate))
+}
+
+/// Returns the lifetime parameters, which are the only generics supported by
+/// `#[extern_trait]`.
+fn lifetime_params(generics: &Generics) -> Result<Vec<Lifetime>> {
+    if let Some(where_clause) = &generics.where_clause {
+        return Err(Error::new_spanned(
+            where_clause,
+            "where clauses are not supported for #[extern_trait]",
+        ));
+    }
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) if param.bounds.is_empty() => Ok(param.lifetime.clone()),
+            _ => Err(Error::new_spanned(
+                param,
+                "only lifetime parameters without bounds are supported for #[extern_trait]",
+            )),
+        })
+        .collect()
+}
+
+fn generate_proxy_impl(
+    proxy_name: &Ident,
+    proxy_ty: &Type,
+    export_name: &str,
+    linkage: &Linkage,
+    lifetimes: &[Lifetime],
+    sig: &Signature,
+    shared: Option<&Ident>,
+) -> Result<(TokenStream, TokenStream)> {
+    let mut sig = sig.clone();
+    let ident = &sig.ident;
+
+    let args = sig
+        .inputs
+        .iter_mut()
+        .enumerate()
+        .map(|(i, arg)| match arg {
+            FnArg::Receiver(_) => format_ident!("self"),
+            FnArg::Typed(arg) => {
+                let name = format_ident!("_{}", i);
+                arg.pat = parse_quote!(#name);
+                name
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let proxy = Box::new(proxy_ty.clone());
+
+    let (mut output, ret) = match &sig.output {
+        ReturnType::Default => (ReturnType::Default, None),
+        ReturnType::Type(arr, ty) => {
+            if ty.contains_self() {
+                if let Some(kind) = ty.self_kind() {
+                    (
+                        ReturnType::Type(*arr, kind.into_abi_type_for(proxy.clone())),
+                        Some(kind),
+                    )
+                } else {
+                    return Err(Error::new_spanned(
+                        ty,
+                        "Too complex return type for #[extern_trait]",
+                    ));
+                }
+            } else {
+                (ReturnType::Type(*arr, ty.clone()), None)
+            }
+        }
+    };
+
+    let (mut inputs, args): (Vec<_>, Vec<_>) = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Receiver(arg) => &arg.ty,
+            FnArg::Typed(arg) => &arg.ty,
+        })
+        .zip(args)
+        .map(|(ty, arg)| {
+            if ty.contains_self() {
+                if let Some(kind) = ty.self_kind() {
+                    if matches!(kind, SelfKind::Value) {
+                        return Err(Error::new_spanned(
+                            ty,
+                            "Passing `Self` by value is not supported for #[extern_trait] yet",
+                        ));
+                    }
+                    if matches!(kind, SelfKind::Tuple(_)) {
+                        return Err(Error::new_spanned(
+                            ty,
+                            "Passing tuples containing `Self` is not supported for #[extern_trait] yet",
+                        ));
+                    }
+                    Ok((
+                        kind.into_abi_type_for(proxy.clone()),
+                        kind.wrap_abi(arg.into_token_stream()),
+                    ))
+                } else {
+                    Err(Error::new_spanned(
+                        ty,
+                        "Too complex argument type for #[extern_trait]",
+                    ))
+                }
+            } else {
+                Ok((ty.clone(), arg.into_token_stream()))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+
+    let call = match ret {
+        Some(kind) if kind.is_indirect() => {
+            output = ReturnType::Default;
+            let out_types = kind.out_types_for(&proxy);
+            let rets = (0..out_types.len())
+                .map(|i| format_ident!("ret_{}", i))
+                .collect::<Vec<_>>();
+            inputs.extend(out_types.into_iter().map(|ty| parse_quote!(*mut #ty)));
+            let read = kind.read_out(&mut rets.iter().cloned());
+            quote! {
+                #(let mut #rets = ::core::mem::MaybeUninit::uninit();)*
+                #ident(#(#args,)* #(#rets.as_mut_ptr()),*);
+                #read
+            }
+        }
+        Some(kind) => kind.unwrap_abi(quote!(#ident(#(#args),*))),
+        None => quote!(#ident(#(#args),*)),
+    };
+
+    let import = import(
+        export_name,
+        linkage,
+        lifetimes,
+        ident,
+        &inputs,
+        &output,
+        &format!("no implementation is linked for `{proxy_name}::{ident}`"),
+    );
+
+    let Some(shared) = shared else {
+        return Ok((
+            quote! {
+                #sig {
+                    #import
+                    #call
+                }
+            },
+            TokenStream::new(),
+        ));
+    };
+    Ok((
+        quote! {
+            #sig {
+                let #ident = #proxy_name::#shared();
+                #call
+            }
+        },
+        quote! {
+            #[doc(hidden)]
+            fn #shared() -> extern "Rust" fn(#(#inputs),*) #output {
+                #import
+                #ident
+            }
+        },
+    ))
+}
+
+/// The borrowed proxy types, which point to the implementation type instead of holding it.
+struct Views {
+    ref_name: Ident,
+    mut_name: Ident,
+    ref_content: TokenStream,
+    mut_content: TokenStream,
+    /// Imports shared by the proxy and the borrowed proxies.
+    shared_imports: TokenStream,
+}
+
+/// Returns whether the method takes `&mut self` if it takes `&self` or `&mut self` and does
+/// not use `Self` otherwise, so that the borrowed proxy types can forward it.
+fn borrowed_receiver(sig: &Signature) -> Option<bool> {
+    let Some(FnArg::Receiver(receiver)) = sig.inputs.first() else {
+        return None;
+    };
+    let Some(SelfKind::Ref { mutability, .. }) = receiver.ty.self_kind() else {
+        return None;
+    };
+    let others = sig.inputs.iter().skip(1).any(|arg| match arg {
+        FnArg::Receiver(_) => true,
+        FnArg::Typed(arg) => arg.ty.contains_self(),
+    });
+    let output = matches!(&sig.output, ReturnType::Type(_, ty) if ty.contains_self());
+    (!others && !output).then_some(mutability.is_some())
+}
+
+/// Generates a method taking `&self` or `&mut self` for the borrowed proxy types, which
+/// passes their pointer to the function returned by `shared`, the import of the proxy.
+fn generate_borrowed_impl(
+    proxy_name: &Ident,
+    shared: &Ident,
+    f: &TraitItemFn,
+    mutable: bool,
+) -> TokenStream {
+    let mut sig = f.sig.clone();
+    let ident = &sig.ident;
+    let docs = f
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect::<Vec<_>>();
+
+    let args = sig
+        .inputs
+        .iter_mut()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(i, arg)| match arg {
+            FnArg::Receiver(_) => None,
+            FnArg::Typed(arg) => {
+                let name = format_ident!("_{}", i);
+                arg.pat = parse_quote!(#name);
+                Some(name)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let (this, this_ty): (TokenStream, Type) = if mutable {
+        (
+            quote!(self.0.as_ptr().cast()),
+            parse_quote!(*mut #proxy_name),
+        )
+    } else {
+        (
+            quote!(self.0.as_ptr().cast_const().cast()),
+            parse_quote!(*const #proxy_name),
+        )
+    };
+    // The implementation may be smaller than the proxy, so the receiver is passed as a
+    // pointer instead of a reference. The other types are inferred, so that lifetimes
+    // elided in the signature are tied to `self` as usual.
+    let holes = args.iter().map(|_| quote!(_));
+    let holes = quote!(#(, #holes)*);
+
+    quote! {
+        #(#docs)*
+        pub #sig {
+            let #ident = unsafe {
+                ::core::mem::transmute::<
+                    extern "Rust" fn(_ #holes) -> _,
+                    extern "Rust" fn(#this_ty #holes) -> _,
+                >(#proxy_name::#shared())
+            };
+            #ident(#this, #(#args),*)
+        }
+    }
+}
+
+/// How the proxy type imports the symbols of the implementation.
+enum Linkage<'a> {
+    /// The `sentinel` symbol, if any, is referenced as well, so that the linker names the
+    /// problem if no implementation is linked.
+    Strong { sentinel: Option<&'a str> },
+    /// With `weak`, a missing implementation is only detected when calling it.
+    Weak,
+}
+
+/// Declares the function `ident` imported from `export_name`, whose types may use
+/// `lifetimes`.
+///
+/// With [`Linkage::Weak`], calling it panics with `missing` if no implementation is linked.
+fn import(
+    export_name: &str,
+    linkage: &Linkage,
+    lifetimes: &[Lifetime],
+    ident: &Ident,
+    inputs: &[Box<Type>],
+    output: &ReturnType,
+    missing: &str,
+) -> TokenStream {
+    match linkage {
+        // Statics may not use the lifetimes of the surrounding impl, so the function is
+        // imported untyped then.
+        Linkage::Weak if !lifetimes.is_empty() => quote! {
+            let #ident = {
+                unsafe extern "Rust" {
+                    #[linkage = "extern_weak"]
+                    #[link_name = #export_name]
+                    static IMPORT: ::core::option::Option<unsafe extern "Rust" fn()>;
+                }
+                unsafe {
+                    ::core::mem::transmute::<_, extern "Rust" fn(#(#inputs),*) #output>(
+                        IMPORT.expect(#missing),
+                    )
+                }
+            };
+        },
+        Linkage::Weak => quote! {
+            let #ident = {
+                unsafe extern "Rust" {
+                    #[linkage = "extern_weak"]
+                    #[link_name = #export_name]
+                    static IMPORT: ::core::option::Option<extern "Rust" fn(#(#inputs),*) #output>;
+                }
+                unsafe { IMPORT }.expect(#missing)
+            };
+        },
+        Linkage::Strong { sentinel } => {
+            let generics = (!lifetimes.is_empty()).then(|| quote!(<#(#lifetimes),*>));
+            let sentinel = sentinel.map(|sentinel| {
+                quote! {
+                    unsafe extern "Rust" {
+                        #[link_name = #sentinel]
+                        static SENTINEL: u8;
+                    }
+                    ::core::hint::black_box(&raw const SENTINEL);
+                }
+            });
+            quote! {
+                unsafe extern "Rust" {
+                    #[link_name = #export_name]
+                    safe fn #ident #generics(#(_: #inputs),*) #output;
+                }
+                #sentinel
+            }
+        }
+    }
+}
+
+fn generate_macro_rules(
+    trait_: Option<TokenStream>,
+    export_name: &str,
+    sig: &Signature,
+    generics: &Option<TokenStream>,
+    static_ty: &TokenStream,
+) -> TokenStream {
+    let ident = &sig.ident;
+
+    let placeholder = Box::new(Type::Verbatim(quote!($ty)));
+    let mut exact_layout = false;
+
+    let (output, ret) = match &sig.output {
+        ReturnType::Default => (ReturnType::Default, None),
+        ReturnType::Type(arr, ty) => {
+            if ty.contains_self() {
+                let kind = ty.self_kind().unwrap();
+                exact_layout |= kind.needs_exact_layout();
+                if kind.is_indirect() {
+                    (ReturnType::Default, Some(kind))
+                } else {
+                    (
+                        ReturnType::Type(*arr, kind.into_abi_type_for(placeholder.clone())),
+                        Some(kind),
+                    )
+                }
+            } else {
+                (ReturnType::Type(*arr, ty.clone()), None)
+            }
+        }
+    };
+
+    let (mut args, (mut arg_tys, arg_exprs)): (Vec<_>, (Vec<_>, Vec<_>)) = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Receiver(arg) => &arg.ty,
+            FnArg::Typed(arg) => &arg.ty,
+        })
+        .enumerate()
+        .map(|(i, ty)| {
+            let arg = format_ident!("_{}", i);
+            if ty.contains_self() {
+                let kind = ty.self_kind().unwrap();
+                exact_layout |= kind.needs_exact_layout();
+                let expr = kind.unwrap_abi(arg.to_token_stream());
+                (arg, (kind.into_abi_type_for(placeholder.clone()), expr))
+            } else {
+                let expr = arg.to_token_stream();
+                (arg, (ty.clone(), expr))
+            }
+        })
+        .unzip();
+
+    let trait_ = trait_.unwrap_or_else(|| quote!($trait));
+
+    let call = quote!(<$ty as #trait_>::#ident(#(#arg_exprs),*));
+    let body = match ret {
+        Some(kind) if kind.is_indirect() => {
+            let out_types = kind.out_types_for(&placeholder);
+            let rets = (0..out_types.len())
+                .map(|i| format_ident!("ret_{}", i))
+                .collect::<Vec<_>>();
+            args.extend(rets.iter().cloned());
+            arg_tys.extend(
+                out_types
+                    .into_iter()
+                    .map(|ty| Box::new(Type::Verbatim(quote!(*mut #ty)))),
+            );
+            let write = kind.write_out(call, &mut rets.into_iter());
+            quote!(unsafe { #write })
+        }
+        Some(kind) => kind.wrap_abi(call),
+        None => call,
+    };
+
+    let assert_layout = exact_layout.then(|| {
+        let message = format!(
+            " must have the same size and alignment as the proxy to be used with `{ident}`"
+        );
+        quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#static_ty>() == ::core::mem::size_of::<[*const (); 2]>()
+                    && ::core::mem::align_of::<#static_ty>() == ::core::mem::align_of::<[*const (); 2]>(),
+                concat!(stringify!($ty), #message)
+            );
+        }
+    });
+
+    quote! {
+        #assert_layout
+
+        #[doc(hidden)]
+        #[unsafe(export_name = #export_name)]
+        unsafe extern "Rust" fn #ident #generics(#(#args: #arg_tys),*) #output {
+            #body
+        }
+    }
+}
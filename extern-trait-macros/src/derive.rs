@@ -0,0 +1,111 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Path, Result, Type, WherePredicate, parenthesized,
+    parse_quote_spanned, spanned::Spanned, token,
+};
+
+use crate::args;
+
+pub fn register_safe(input: DeriveInput) -> Result<TokenStream> {
+    let krate = args::runtime_path(take_crate(&input, "register_safe")?);
+
+    let fields: Vec<Type> = match &input.data {
+        Data::Struct(data) => data.fields.iter().map(|f| f.ty.clone()).collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|v| &v.fields)
+            .map(|f| f.ty.clone())
+            .collect(),
+        Data::Union(data) => data.fields.named.iter().map(|f| f.ty.clone()).collect(),
+    };
+
+    // Bounds on the field types rather than on the generic parameters, so that every
+    // field is checked, including those of concrete types.
+    Ok(bounded_impl(input, &fields, &quote!(#krate::RegisterSafe)))
+}
+
+pub fn non_null_first(input: DeriveInput) -> Result<TokenStream> {
+    let krate = args::runtime_path(take_crate(&input, "non_null_first")?);
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            "NonNullFirst can only be derived for structs",
+        ));
+    };
+    // Only these guarantee that the first field is at offset 0.
+    let mut repr = false;
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                repr |= meta.path.is_ident("C") || meta.path.is_ident("transparent");
+                // Skip arguments such as `align(8)`.
+                if meta.input.peek(token::Paren) {
+                    let args;
+                    parenthesized!(args in meta.input);
+                    args.parse::<TokenStream>()?;
+                }
+                Ok(())
+            })?;
+        }
+    }
+    if !repr {
+        return Err(Error::new(
+            Span::call_site(),
+            "NonNullFirst requires #[repr(C)] or #[repr(transparent)]",
+        ));
+    }
+    let Some(first) = data.fields.iter().next() else {
+        return Err(Error::new(
+            Span::call_site(),
+            "NonNullFirst requires at least one field",
+        ));
+    };
+
+    let fields = [first.ty.clone()];
+    Ok(bounded_impl(input, &fields, &quote!(#krate::NonNullFirst)))
+}
+
+/// Implements the unsafe marker trait `trait_` if all of `fields` implement it.
+fn bounded_impl(mut input: DeriveInput, fields: &[Type], trait_: &TokenStream) -> TokenStream {
+    let predicates = fields
+        .iter()
+        .map(|ty| parse_quote_spanned!(ty.span()=> #ty: #trait_))
+        .collect::<Vec<WherePredicate>>();
+    input
+        .generics
+        .make_where_clause()
+        .predicates
+        .extend(predicates);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        unsafe impl #impl_generics #trait_ for #name #ty_generics
+            #where_clause
+        {
+        }
+    }
+}
+
+/// Parses `#[attr(crate = path)]`.
+fn take_crate(input: &DeriveInput, attr: &str) -> Result<Option<Path>> {
+    let mut krate = None;
+    for a in &input.attrs {
+        if !a.path().is_ident(attr) {
+            continue;
+        }
+        a.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("crate") {
+                return Err(meta.error(format!("unknown #[{attr}] argument")));
+            }
+            if krate.replace(meta.value()?.parse()?).is_some() {
+                return Err(meta.error(format!("duplicate #[{attr}] argument")));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(krate)
+}
@@ -0,0 +1,128 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    GenericArgument, GenericParam, ItemImpl, Lifetime, PathArguments, Result, Type,
+    spanned::Spanned,
+};
+
+use crate::lifetime;
+
+pub fn expand(input: ItemImpl) -> Result<TokenStream> {
+    let Some((_, trait_, _)) = &input.trait_ else {
+        return Err(syn::Error::new(Span::call_site(), "expected a trait impl"));
+    };
+
+    let lifetimes = input
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) if param.bounds.is_empty() => Ok(param.lifetime.clone()),
+            _ => Err(syn::Error::new_spanned(
+                &input.generics,
+                "#[extern_trait] impls may only be generic over the lifetimes of the trait",
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(where_clause) = &input.generics.where_clause {
+        return Err(syn::Error::new_spanned(
+            where_clause,
+            "#[extern_trait] impls may not have a where clause",
+        ));
+    }
+
+    // The lifetimes of the trait are passed to the exported macro, which also needs the path
+    // of the macro without them.
+    let mut macro_path = trait_.clone();
+    let last = macro_path.segments.last_mut().unwrap();
+    let args = match std::mem::replace(&mut last.arguments, PathArguments::None) {
+        PathArguments::None => Vec::new(),
+        PathArguments::AngleBracketed(args) => args.args.into_iter().collect(),
+        PathArguments::Parenthesized(args) => {
+            return Err(syn::Error::new_spanned(
+                args,
+                "expected an #[extern_trait] trait",
+            ));
+        }
+    };
+    let trait_lifetimes = args
+        .iter()
+        .map(|arg| match arg {
+            GenericArgument::Lifetime(lifetime) => Some(lifetime.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if trait_lifetimes.as_ref() != Some(&lifetimes) {
+        return Err(syn::Error::new_spanned(
+            trait_,
+            "the lifetimes of #[extern_trait] impls must be the lifetime arguments of the trait",
+        ));
+    }
+
+    let ty = &input.self_ty;
+    let invocation = if lifetimes.is_empty() {
+        quote!(#macro_path!(#trait_: #ty);)
+    } else {
+        let static_ty = static_type(ty, &lifetimes);
+        let covariant = covariant(ty, &lifetimes);
+        quote! {
+            #covariant
+
+            #macro_path!(#trait_: #ty, #(#lifetimes),*; #static_ty);
+        }
+    };
+    let assert_size = assert_size(&static_type(ty, &lifetimes));
+
+    Ok(quote! {
+        #input
+
+        #assert_size
+
+        #invocation
+    })
+}
+
+/// Returns `ty` with `lifetimes` replaced by `'static`, for the items that may not depend on
+/// them, such as the `TypeId` of the implementor.
+fn static_type(ty: &Type, lifetimes: &[Lifetime]) -> Type {
+    lifetime::replace(ty, |lt| {
+        lifetimes
+            .contains(lt)
+            .then(|| Lifetime::new("'static", lt.span()))
+    })
+}
+
+/// Asserts that `ty` is covariant in `lifetimes`, as the proxy type is, so that shortening
+/// the lifetimes of the proxy does not shorten those of the implementor unsoundly.
+fn covariant(ty: &Type, lifetimes: &[Lifetime]) -> TokenStream {
+    let long = Lifetime::new("'__long", Span::call_site());
+    let short = Lifetime::new("'__short", Span::call_site());
+    let long_ty = lifetime::replace(ty, |lt| lifetimes.contains(lt).then(|| long.clone()));
+    let short_ty = lifetime::replace(ty, |lt| lifetimes.contains(lt).then(|| short.clone()));
+    quote_spanned! {ty.span()=>
+        const _: () = {
+            #[allow(dead_code)]
+            fn covariant<#long: #short, #short>(value: #long_ty) -> #short_ty {
+                value
+            }
+        };
+    }
+}
+
+/// Asserts that an implementor fits into the proxy type, which is also not aligned more
+/// strictly than the implementor requires.
+pub fn assert_size(ty: &Type) -> TokenStream {
+    quote_spanned! {ty.span()=>
+        const _: () = {
+            assert!(
+                ::core::mem::size_of::<#ty>() <= ::core::mem::size_of::<usize>() * 2,
+                concat!(stringify!(#ty), " is too large to be used with #[extern_trait]")
+            );
+            assert!(
+                ::core::mem::align_of::<#ty>() <= ::core::mem::align_of::<[*const (); 2]>(),
+                concat!(stringify!(#ty), " is too strictly aligned to be used with #[extern_trait]")
+            );
+        };
+    }
+}
@@ -0,0 +1,41 @@
+//! Procedural macros of the [`extern-trait`](https://docs.rs/extern-trait) crate, which
+//! re-exports them and documents their usage.
+
+mod args;
+mod decl;
+mod derive;
+mod imp;
+mod lifetime;
+mod symbol;
+mod ty;
+
+use proc_macro::TokenStream;
+use syn::{Error, parse_macro_input};
+
+#[proc_macro_attribute]
+pub fn extern_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        let args = parse_macro_input!(args as args::Args);
+        decl::expand(args, parse_macro_input!(input))
+    } else {
+        imp::expand(parse_macro_input!(input))
+    }
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+/// Derives `extern_trait::RegisterSafe`.
+#[proc_macro_derive(RegisterSafe, attributes(register_safe))]
+pub fn register_safe(input: TokenStream) -> TokenStream {
+    derive::register_safe(parse_macro_input!(input))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `extern_trait::NonNullFirst`.
+#[proc_macro_derive(NonNullFirst, attributes(non_null_first))]
+pub fn non_null_first(input: TokenStream) -> TokenStream {
+    derive::non_null_first(parse_macro_input!(input))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
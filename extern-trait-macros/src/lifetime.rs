@@ -0,0 +1,59 @@
+use proc_macro2::{Group, Punct, Spacing, TokenStream, TokenTree};
+use quote::format_ident;
+use syn::{Ident, Lifetime, Type, visit_mut::VisitMut};
+
+/// Returns a copy of `ty` with the lifetimes for which `f` returns a replacement replaced.
+pub fn replace(ty: &Type, f: impl Fn(&Lifetime) -> Option<Lifetime>) -> Type {
+    struct Replace<F>(F);
+
+    impl<F: Fn(&Lifetime) -> Option<Lifetime>> VisitMut for Replace<F> {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if let Some(replacement) = (self.0)(lifetime) {
+                *lifetime = replacement;
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    Replace(f).visit_type_mut(&mut ty);
+    ty
+}
+
+/// Returns the name of the `macro_rules!` metavariable standing in for a lifetime of the trait.
+pub fn metavar(lifetime: &Lifetime) -> Ident {
+    format_ident!("lt_{}", lifetime.ident)
+}
+
+/// Replaces `lifetimes` in `tokens` by their [metavariables](metavar), so that generated
+/// `macro_rules!` use the lifetimes of the impl instead of those of the trait.
+pub fn into_metavars(tokens: TokenStream, lifetimes: &[Lifetime]) -> TokenStream {
+    let mut output = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                let lifetime = match tokens.peek() {
+                    Some(TokenTree::Ident(ident)) => lifetimes.iter().find(|lt| lt.ident == *ident),
+                    _ => None,
+                };
+                let Some(lifetime) = lifetime else {
+                    output.push(TokenTree::Punct(punct));
+                    continue;
+                };
+                tokens.next();
+                let mut dollar = Punct::new('$', Spacing::Alone);
+                dollar.set_span(punct.span());
+                output.push(TokenTree::Punct(dollar));
+                output.push(TokenTree::Ident(metavar(lifetime)));
+            }
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    Group::new(group.delimiter(), into_metavars(group.stream(), lifetimes));
+                replaced.set_span(group.span());
+                output.push(TokenTree::Group(replaced));
+            }
+            token => output.push(token),
+        }
+    }
+    output.into_iter().collect()
+}
@@ -0,0 +1,352 @@
+use std::path::Path;
+
+use proc_macro2::Span;
+use quote::{ToTokens, quote};
+use syn::{
+    Error, FnArg, Ident, LitStr, Result, ReturnType, Signature, Type, ext::IdentExt, parse_quote,
+};
+
+use crate::{args::AbiVersion, ty::TypeExt};
+
+/// Returns a string that tells apart traits of the same name in one crate.
+///
+/// This is either the user-supplied namespace or a hash of the file declaring the trait,
+/// relative to the package. Unlike the line, the file does not change with unrelated edits.
+pub fn disambiguator(namespace: Option<&LitStr>, trait_name: &Ident) -> Result<String> {
+    if let Some(namespace) = namespace {
+        let value = namespace.value();
+        if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::new_spanned(
+                namespace,
+                "namespace may only contain ASCII letters, digits and underscores",
+            ));
+        }
+        return Ok(value);
+    }
+
+    let file = trait_name.span().unwrap().file();
+    let file = package_relative(
+        Path::new(&file),
+        std::env::var_os("CARGO_MANIFEST_DIR")
+            .as_deref()
+            .map(Path::new),
+        std::env::current_dir().ok().as_deref(),
+    );
+
+    Ok(format!("{:016x}", hash(&file)))
+}
+
+/// Returns the path of `file` relative to the package directory, with `/` as separator.
+///
+/// Cargo passes paths relative to the workspace root for workspace members, which is the
+/// working directory of the compiler, and absolute paths for other packages.
+fn package_relative(file: &Path, manifest_dir: Option<&Path>, cwd: Option<&Path>) -> String {
+    let absolute = match cwd {
+        Some(cwd) if file.is_relative() => cwd.join(file),
+        _ => file.to_path_buf(),
+    };
+    let file = manifest_dir
+        .and_then(|dir| absolute.strip_prefix(dir).ok())
+        .unwrap_or(file);
+    file.to_string_lossy().replace('\\', "/")
+}
+
+/// Returns the package and crate components of the symbol prefix.
+///
+/// Outside of Cargo, the identity of the declaring crate has to be given through the
+/// `crate_id` argument, which takes the place of the package and leaves the crate empty.
+pub fn crate_id(crate_id: Option<&LitStr>) -> Result<[String; 2]> {
+    if let Some(crate_id) = crate_id {
+        let value = crate_id.value();
+        if value.is_empty() {
+            return Err(Error::new_spanned(crate_id, "crate_id may not be empty"));
+        }
+        return Ok([value, String::new()]);
+    }
+    match (
+        std::env::var("CARGO_PKG_NAME"),
+        std::env::var("CARGO_CRATE_NAME"),
+    ) {
+        (Ok(package), Ok(crate_name)) => Ok([package, crate_name]),
+        _ => Err(Error::new(
+            Span::call_site(),
+            "cannot determine the crate declaring this #[extern_trait] outside of Cargo; \
+             specify `crate_id = \"...\"`",
+        )),
+    }
+}
+
+/// Returns the version component of the symbol prefix.
+///
+/// By default this is the exact package version, so that any two versions of the
+/// declaring crate are considered incompatible.
+pub fn version(abi_version: Option<&AbiVersion>) -> Result<String> {
+    let package = std::env::var("CARGO_PKG_VERSION");
+    match abi_version {
+        None => package.map_err(|_| {
+            Error::new(
+                Span::call_site(),
+                "cannot determine the version of the crate declaring this #[extern_trait] \
+                 outside of Cargo; specify `abi_version`",
+            )
+        }),
+        Some(AbiVersion::Explicit(version)) => {
+            let value = version.value();
+            if value.is_empty() {
+                return Err(Error::new_spanned(version, "abi_version may not be empty"));
+            }
+            Ok(format!("abi:{value}"))
+        }
+        Some(AbiVersion::Semver(ident)) => {
+            let Ok(package) = package else {
+                return Err(Error::new_spanned(
+                    ident,
+                    "`abi_version = semver` requires CARGO_PKG_VERSION to be set",
+                ));
+            };
+            // Same rule as Cargo: the leftmost non-zero component decides compatibility.
+            let numbers = package
+                .split(['-', '+'])
+                .next()
+                .unwrap_or_default()
+                .split('.')
+                .collect::<Vec<_>>();
+            let len = numbers
+                .iter()
+                .position(|n| *n != "0")
+                .map_or(numbers.len(), |i| i + 1);
+            Ok(format!("semver:{}", numbers[..len].join(".")))
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which is stable across compiler versions and platforms.
+pub fn hash(data: &str) -> u64 {
+    data.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns the prefix shared by all symbols of a trait.
+///
+/// Symbol names are `__extern_trait_` followed by a sequence of [encoded](encode)
+/// components: the package name, the [version], the crate name (see [crate_id]), the
+/// [disambiguator], the trait name, the item name and, for methods, a hash of the signature.
+pub fn prefix(components: &[&str]) -> String {
+    let mut prefix = "__extern_trait_".to_string();
+    for component in components {
+        prefix.push_str(&encode(component));
+    }
+    prefix
+}
+
+/// Validates a symbol name given by the user, which is used verbatim.
+pub fn verbatim(symbol: &LitStr) -> Result<String> {
+    let value = symbol.value();
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.')
+    {
+        return Err(Error::new_spanned(
+            symbol,
+            "symbol may only contain ASCII letters, digits, `_`, `$` and `.`",
+        ));
+    }
+    Ok(value)
+}
+
+/// Returns the name of a symbol that only exists to make linker errors readable, e.g.
+/// `MISSING_IMPL_OF_extern_trait_Hello_from_crate_a_0123456789abcdef`.
+///
+/// The trailing hash of the symbol prefix tells apart traits of the same name. Characters of
+/// the trait name outside of `[A-Za-z0-9_]` are escaped as `_u` followed by their hex code,
+/// e.g. `Größe` becomes `Gr_uf6_udfe`.
+pub fn sentinel(problem: &str, trait_name: &Ident, crate_name: &str, prefix: &str) -> String {
+    let crate_name = crate_name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    let mut trait_name = trait_name.unraw().to_string();
+    if !trait_name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        trait_name = trait_name
+            .chars()
+            .map(|c| match c {
+                'A'..='Z' | 'a'..='z' | '0'..='9' | '_' => c.to_string(),
+                _ => format!("_u{:x}", c as u32),
+            })
+            .collect();
+    }
+    format!(
+        "{problem}_IMPL_OF_extern_trait_{trait_name}_from_{crate_name}_{:016x}",
+        hash(prefix)
+    )
+}
+
+/// Returns the name of an item of the trait.
+///
+/// Items generated by `#[extern_trait]` itself start with `$`, so they can never clash
+/// with method names.
+pub fn item(prefix: &str, name: &str) -> String {
+    format!("{prefix}{}", encode(name))
+}
+
+/// Returns the export name of a method, which includes a hash of its signature so that
+/// mismatched declarations fail to link instead of causing undefined behavior.
+///
+/// Set `EXTERN_TRAIT_PRINT_SIGNATURES` when building to print the signature behind each hash.
+/// The variable is not tracked, so changing it does not rebuild crates that are up to date.
+pub fn export_name(prefix: &str, sig: &Signature) -> String {
+    let (export_name, line) = export_name_and_signature(prefix, sig);
+    if std::env::var_os("EXTERN_TRAIT_PRINT_SIGNATURES").is_some() {
+        eprintln!("{line}");
+    }
+    export_name
+}
+
+/// Returns the export name of a method and the line printed for it with
+/// `EXTERN_TRAIT_PRINT_SIGNATURES`.
+fn export_name_and_signature(prefix: &str, sig: &Signature) -> (String, String) {
+    let signature = normalize(sig);
+    let export_name = format!(
+        "{}{}",
+        item(prefix, &sig.ident.unraw().to_string()),
+        encode(&format!("{:016x}", hash(&signature)))
+    );
+    let line = format!("{export_name}: {signature}");
+    (export_name, line)
+}
+
+/// Encodes an arbitrary string as a length-prefixed component made of `[A-Za-z0-9_]` only.
+///
+/// `_` is escaped as `__` and every other byte outside of `[A-Za-z0-9]` as `_` followed by two
+/// lowercase hex digits. Like in the v0 mangling scheme, the length is separated by a `_` if the
+/// escaped string starts with a digit or `_`, e.g. `0.2.0` becomes `9_0_2e2_2e0`. It is also
+/// separated for the empty string, so that the length of the next component does not continue
+/// it.
+pub fn encode(component: &str) -> String {
+    let mut escaped = String::new();
+    for byte in component.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => escaped.push(byte as char),
+            b'_' => escaped.push_str("__"),
+            _ => escaped.push_str(&format!("_{byte:02x}")),
+        }
+    }
+    let separator =
+        if escaped.is_empty() || escaped.starts_with(|c: char| c.is_ascii_digit() || c == '_') {
+            "_"
+        } else {
+            ""
+        };
+    format!("{}{separator}{escaped}", escaped.len())
+}
+
+/// Renders the signature as seen across the extern boundary, e.g. `fn (& Self , u32) -> u64`.
+fn normalize(sig: &Signature) -> String {
+    let self_ty: Box<Type> = parse_quote!(Self);
+    let normalize = |ty: &Type| match ty.self_kind() {
+        Some(kind) if ty.contains_self() => kind.into_type_for(self_ty.clone()).to_token_stream(),
+        _ => ty.to_token_stream(),
+    };
+
+    let inputs = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Receiver(arg) => normalize(&arg.ty),
+        FnArg::Typed(arg) => normalize(&arg.ty),
+    });
+    let output = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => {
+            let ty = normalize(ty);
+            Some(quote!(-> #ty))
+        }
+    };
+
+    quote!(fn(#(#inputs),*) #output).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::format_ident;
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn export_name(sig: Signature) -> String {
+        super::export_name("__extern_trait_", &sig)
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(""), "0_");
+        assert_eq!(encode("Counter"), "7Counter");
+        assert_eq!(encode("abi_version"), "12abi__version");
+        assert_eq!(encode("extern-trait"), "14extern_2dtrait");
+        assert_eq!(encode("AsRef<str>"), "14AsRef_3cstr_3e");
+        assert_eq!(encode("ö"), "6__c3_b6");
+        // The length is separated from escaped strings starting with a digit or `_`.
+        assert_eq!(encode("0.2.0"), "9_0_2e2_2e0");
+        assert_eq!(encode("_x"), "3___x");
+        assert_eq!(encode("$drop"), "7__24drop");
+    }
+
+    #[test]
+    fn test_package_relative() {
+        let manifest_dir = Path::new("/work/crates/foo");
+        let relative = |file: &str, cwd: &str| {
+            package_relative(Path::new(file), Some(manifest_dir), Some(Path::new(cwd)))
+        };
+        // Workspace members are passed relative to the workspace root.
+        assert_eq!(relative("crates/foo/src/lib.rs", "/work"), "src/lib.rs");
+        assert_eq!(relative("/work/crates/foo/src/lib.rs", "/"), "src/lib.rs");
+        assert_eq!(relative("src/lib.rs", "/work/crates/foo"), "src/lib.rs");
+        // Files outside of the package are kept as they are.
+        assert_eq!(relative("/other/src/lib.rs", "/work"), "/other/src/lib.rs");
+        assert_eq!(
+            package_relative(Path::new("src/lib.rs"), None, None),
+            "src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_sentinel() {
+        let sentinel =
+            |name: &str| super::sentinel("MISSING", &format_ident!("{name}"), "my-crate", "prefix");
+        let hash = format!("{:016x}", hash("prefix"));
+        assert_eq!(
+            sentinel("Counter"),
+            format!("MISSING_IMPL_OF_extern_trait_Counter_from_my_crate_{hash}")
+        );
+        assert_eq!(
+            sentinel("Größe"),
+            format!("MISSING_IMPL_OF_extern_trait_Gr_uf6_udfe_from_my_crate_{hash}")
+        );
+    }
+
+    #[test]
+    fn test_signature_hash() {
+        assert_ne!(
+            export_name(parse_quote!(fn get(&self) -> u32)),
+            export_name(parse_quote!(fn get(&self) -> u64)),
+        );
+        assert_ne!(
+            export_name(parse_quote!(fn set(&mut self, value: u32))),
+            export_name(parse_quote!(fn set(&self, value: u32))),
+        );
+        // Only the types matter, and `Self` is the same however the receiver is written.
+        assert_eq!(
+            export_name(parse_quote!(fn set(&mut self, value: u32))),
+            export_name(parse_quote!(fn set(self: &mut Self, other: u32))),
+        );
+    }
+
+    #[test]
+    fn test_printed_signature() {
+        let sig = parse_quote!(fn get(&self, index: usize) -> Option<u32>);
+        let (export_name, line) = export_name_and_signature("__extern_trait_", &sig);
+        let (printed_name, signature) = line.split_once(": ").unwrap();
+        assert_eq!(printed_name, export_name);
+        assert_eq!(signature, "fn (& Self , usize) -> Option < u32 >");
+        assert!(export_name.ends_with(&encode(&format!("{:016x}", hash(signature)))));
+    }
+}
@@ -0,0 +1,559 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Expr, GenericArgument, Ident, Index, Lifetime, PathArguments, ReturnType, Token, Type,
+    TypeArray, TypeBareFn, TypePath, TypePtr, TypeReference, TypeSlice, TypeTuple, parse_quote,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SelfKind<'a> {
+    Value,
+    Ptr {
+        star_token: &'a Token![*],
+        const_token: &'a Option<Token![const]>,
+        mutability: &'a Option<Token![mut]>,
+    },
+    Ref {
+        and_token: &'a Token![&],
+        lifetime: &'a Option<Lifetime>,
+        mutability: &'a Option<Token![mut]>,
+    },
+    Pin {
+        and_token: &'a Token![&],
+        lifetime: &'a Option<Lifetime>,
+        mutability: &'a Option<Token![mut]>,
+    },
+    /// `Box<Self>`, `Rc<Self>` or `Arc<Self>`.
+    Smart {
+        path: &'a TypePath,
+        mutability: Option<Token![mut]>,
+    },
+    /// `&[Self]` or `&mut [Self]`.
+    Slice {
+        and_token: &'a Token![&],
+        lifetime: &'a Option<Lifetime>,
+        mutability: &'a Option<Token![mut]>,
+    },
+    /// `[Self; N]`.
+    Array {
+        len: &'a Expr,
+    },
+    /// A tuple containing any of the above, e.g. `(Self, usize)`.
+    Tuple(&'a TypeTuple),
+    /// A function pointer whose parameters and return type are pointers to `Self`, e.g.
+    /// `fn(&Self, u32)`.
+    ///
+    /// The function pointer is passed through unchanged instead of through a trampoline
+    /// converting the implementor reference into a proxy reference: the callback is only known
+    /// at runtime, so a trampoline would have to look it up somewhere, and the implementor only
+    /// receives a single function pointer. Passing the callback itself is sound because such
+    /// function pointers are ABI-compatible with each other regardless of the pointee, as long
+    /// as the implementor has the layout of the proxy.
+    Fn(&'a TypeBareFn),
+}
+
+impl SelfKind<'_> {
+    pub fn into_type_for(self, elem: Box<Type>) -> Box<Type> {
+        match self {
+            SelfKind::Value => elem,
+            SelfKind::Ptr {
+                star_token,
+                const_token,
+                mutability,
+            } => Box::new(Type::Ptr(TypePtr {
+                star_token: *star_token,
+                const_token: *const_token,
+                mutability: *mutability,
+                elem,
+            })),
+            SelfKind::Ref {
+                and_token,
+                lifetime,
+                mutability,
+            } => Box::new(Type::Reference(TypeReference {
+                and_token: *and_token,
+                lifetime: lifetime.clone(),
+                mutability: *mutability,
+                elem,
+            })),
+            SelfKind::Pin {
+                and_token,
+                lifetime,
+                mutability,
+            } => Box::new(Type::Verbatim(
+                quote!(::core::pin::Pin<#and_token #lifetime #mutability #elem>),
+            )),
+            SelfKind::Smart { path, .. } => {
+                let mut path = path.clone();
+                if let Some(segment) = path.path.segments.last_mut()
+                    && let PathArguments::AngleBracketed(args) = &mut segment.arguments
+                {
+                    args.args[0] = GenericArgument::Type(*elem);
+                }
+                Box::new(Type::Path(path))
+            }
+            SelfKind::Slice {
+                and_token,
+                lifetime,
+                mutability,
+            } => Box::new(Type::Reference(TypeReference {
+                and_token: *and_token,
+                lifetime: lifetime.clone(),
+                mutability: *mutability,
+                elem: Box::new(Type::Slice(TypeSlice {
+                    bracket_token: Default::default(),
+                    elem,
+                })),
+            })),
+            SelfKind::Array { len } => Box::new(Type::Array(TypeArray {
+                bracket_token: Default::default(),
+                elem,
+                semi_token: Default::default(),
+                len: len.clone(),
+            })),
+            SelfKind::Tuple(tpl) => {
+                let mut tpl = tpl.clone();
+                for elem_ty in &mut tpl.elems {
+                    if let Some(kind) = elem_ty.self_kind() {
+                        *elem_ty = *kind.into_type_for(elem.clone());
+                    }
+                }
+                Box::new(Type::Tuple(tpl))
+            }
+            SelfKind::Fn(f) => {
+                let mut f = f.clone();
+                for arg in &mut f.inputs {
+                    if let Some(kind) = arg.ty.self_kind() {
+                        arg.ty = *kind.into_type_for(elem.clone());
+                    }
+                }
+                if let ReturnType::Type(_, ty) = &mut f.output
+                    && let Some(kind) = ty.self_kind()
+                {
+                    *ty = kind.into_type_for(elem);
+                }
+                Box::new(Type::BareFn(f))
+            }
+        }
+    }
+
+    /// Returns the type used to pass the value across the extern boundary.
+    ///
+    /// Values that are returned [indirectly](Self::is_indirect) are written to a `*mut` of
+    /// [`Self::into_type_for`] instead.
+    pub fn into_abi_type_for(self, elem: Box<Type>) -> Box<Type> {
+        match self {
+            SelfKind::Array { .. } => Box::new(Type::Ptr(TypePtr {
+                star_token: Default::default(),
+                const_token: Some(Default::default()),
+                mutability: None,
+                elem: self.into_type_for(elem),
+            })),
+            SelfKind::Smart { mutability, .. } => {
+                let const_token = mutability.is_none().then(Default::default);
+                Box::new(Type::Ptr(TypePtr {
+                    star_token: Default::default(),
+                    const_token,
+                    mutability,
+                    elem,
+                }))
+            }
+            _ => self.into_type_for(elem),
+        }
+    }
+
+    /// Converts `expr` into the type returned by [`Self::into_abi_type_for`].
+    pub fn wrap_abi(self, expr: TokenStream) -> TokenStream {
+        match self {
+            SelfKind::Smart { path, .. } => {
+                let path = smart_path(path);
+                quote!({
+                    extern crate alloc as __extern_trait_alloc;
+                    #path::into_raw(#expr)
+                })
+            }
+            SelfKind::Array { .. } => quote! {
+                ::core::ptr::from_ref(&::core::mem::ManuallyDrop::new(#expr)).cast()
+            },
+            _ => expr,
+        }
+    }
+
+    /// Converts `expr` back from the type returned by [`Self::into_abi_type_for`].
+    pub fn unwrap_abi(self, expr: TokenStream) -> TokenStream {
+        match self {
+            SelfKind::Smart { path, .. } => {
+                let path = smart_path(path);
+                quote!(unsafe {
+                    extern crate alloc as __extern_trait_alloc;
+                    #path::from_raw(#expr)
+                })
+            }
+            SelfKind::Array { .. } => quote!(unsafe { ::core::ptr::read(#expr) }),
+            _ => expr,
+        }
+    }
+
+    /// Whether the value is returned through out-pointers.
+    pub fn is_indirect(self) -> bool {
+        matches!(self, SelfKind::Array { .. } | SelfKind::Tuple(_))
+    }
+
+    /// Returns the pointee types of the out-pointers an indirect value is returned through.
+    pub fn out_types_for(self, elem: &Type) -> Vec<Type> {
+        match self {
+            SelfKind::Array { .. } => vec![*self.into_type_for(Box::new(elem.clone()))],
+            SelfKind::Tuple(tpl) => tpl
+                .elems
+                .iter()
+                .flat_map(|ty| match ty.self_kind() {
+                    Some(kind) => kind.out_types_for(elem),
+                    None => vec![ty.clone()],
+                })
+                .collect(),
+            _ => vec![*self.into_abi_type_for(Box::new(elem.clone()))],
+        }
+    }
+
+    /// Writes `expr` through the out-pointers in `ptrs`.
+    pub fn write_out(
+        self,
+        expr: TokenStream,
+        ptrs: &mut impl Iterator<Item = Ident>,
+    ) -> TokenStream {
+        match self {
+            SelfKind::Array { .. } => {
+                let ptr = ptrs.next();
+                quote!(#ptr.write(#expr);)
+            }
+            SelfKind::Tuple(tpl) => {
+                let writes = tpl.elems.iter().enumerate().map(|(i, ty)| {
+                    let i = Index::from(i);
+                    match ty.self_kind() {
+                        Some(kind) => kind.write_out(quote!(ret.#i), ptrs),
+                        None => {
+                            let ptr = ptrs.next();
+                            quote!(#ptr.write(ret.#i);)
+                        }
+                    }
+                });
+                quote!({
+                    let ret = #expr;
+                    #(#writes)*
+                })
+            }
+            _ => {
+                let ptr = ptrs.next();
+                let expr = self.wrap_abi(expr);
+                quote!(#ptr.write(#expr);)
+            }
+        }
+    }
+
+    /// Reads the value back from the initialized `MaybeUninit`s in `vals`.
+    pub fn read_out(self, vals: &mut impl Iterator<Item = Ident>) -> TokenStream {
+        match self {
+            SelfKind::Array { .. } => {
+                let val = vals.next();
+                quote!(unsafe { #val.assume_init() })
+            }
+            SelfKind::Tuple(tpl) => {
+                let reads = tpl.elems.iter().map(|ty| match ty.self_kind() {
+                    Some(kind) => kind.read_out(vals),
+                    None => {
+                        let val = vals.next();
+                        quote!(unsafe { #val.assume_init() })
+                    }
+                });
+                quote!((#(#reads,)*))
+            }
+            _ => {
+                let val = vals.next();
+                self.unwrap_abi(quote!(unsafe { #val.assume_init() }))
+            }
+        }
+    }
+
+    /// Whether the implementor has to be layout-compatible with the proxy.
+    pub fn needs_exact_layout(self) -> bool {
+        match self {
+            SelfKind::Smart { .. }
+            | SelfKind::Slice { .. }
+            | SelfKind::Array { .. }
+            | SelfKind::Fn(_) => true,
+            SelfKind::Tuple(tpl) => tpl
+                .elems
+                .iter()
+                .filter_map(Type::self_kind)
+                .any(SelfKind::needs_exact_layout),
+            _ => false,
+        }
+    }
+
+    /// Whether the implementor is pinned, possibly within a tuple or function pointer.
+    pub fn is_pinned(self) -> bool {
+        match self {
+            SelfKind::Pin { .. } => true,
+            SelfKind::Tuple(tpl) => tpl
+                .elems
+                .iter()
+                .filter_map(Type::self_kind)
+                .any(SelfKind::is_pinned),
+            SelfKind::Fn(f) => {
+                let output = match &f.output {
+                    ReturnType::Default => None,
+                    ReturnType::Type(_, ty) => Some(&**ty),
+                };
+                f.inputs
+                    .iter()
+                    .map(|arg| &arg.ty)
+                    .chain(output)
+                    .filter_map(Type::self_kind)
+                    .any(SelfKind::is_pinned)
+            }
+            _ => false,
+        }
+    }
+}
+
+pub trait TypeExt {
+    fn contains_self(&self) -> bool;
+    fn self_kind(&self) -> Option<SelfKind<'_>>;
+}
+
+impl TypeExt for Type {
+    #[allow(clippy::collapsible_if)]
+    fn contains_self(&self) -> bool {
+        match self {
+            Type::Array(arr) => arr.elem.contains_self(),
+            Type::BareFn(f) => {
+                for arg in &f.inputs {
+                    if arg.ty.contains_self() {
+                        return true;
+                    }
+                }
+                if let ReturnType::Type(_, ret) = &f.output {
+                    if ret.contains_self() {
+                        return true;
+                    }
+                }
+                false
+            }
+            Type::Group(group) => group.elem.contains_self(),
+            Type::Paren(paren) => paren.elem.contains_self(),
+            Type::Path(path) => {
+                if let Some(qself) = &path.qself {
+                    if qself.ty.contains_self() {
+                        return true;
+                    }
+                }
+                for segment in &path.path.segments {
+                    if segment.ident == "Self" {
+                        return true;
+                    }
+                    match &segment.arguments {
+                        PathArguments::None => {}
+                        PathArguments::AngleBracketed(args) => {
+                            for arg in &args.args {
+                                if let GenericArgument::Type(ty) = arg {
+                                    if ty.contains_self() {
+                                        return true;
+                                    }
+                                }
+                            }
+                        }
+                        PathArguments::Parenthesized(args) => {
+                            for arg in &args.inputs {
+                                if arg.contains_self() {
+                                    return true;
+                                }
+                            }
+                            if let ReturnType::Type(_, ret) = &args.output {
+                                if ret.contains_self() {
+                                    return true;
+                                }
+                            }
+                        }
+                    }
+                }
+                false
+            }
+            Type::Ptr(ptr) => ptr.elem.contains_self(),
+            Type::Reference(r) => r.elem.contains_self(),
+            Type::Slice(slice) => slice.elem.contains_self(),
+            Type::Tuple(tpl) => {
+                for elem in &tpl.elems {
+                    if elem.contains_self() {
+                        return true;
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn self_kind(&self) -> Option<SelfKind<'_>> {
+        let self_ty = parse_quote!(Self);
+
+        if *self == self_ty {
+            Some(SelfKind::Value)
+        } else if let Type::Ptr(TypePtr {
+            star_token,
+            const_token,
+            mutability,
+            elem,
+        }) = self
+        {
+            if **elem == self_ty {
+                Some(SelfKind::Ptr {
+                    star_token,
+                    const_token,
+                    mutability,
+                })
+            } else {
+                None
+            }
+        } else if let Type::Reference(TypeReference {
+            and_token,
+            lifetime,
+            mutability,
+            elem,
+        }) = self
+        {
+            if **elem == self_ty {
+                Some(SelfKind::Ref {
+                    and_token,
+                    lifetime,
+                    mutability,
+                })
+            } else if let Type::Slice(slice) = &**elem
+                && *slice.elem == self_ty
+            {
+                Some(SelfKind::Slice {
+                    and_token,
+                    lifetime,
+                    mutability,
+                })
+            } else {
+                None
+            }
+        } else if let Type::Array(arr) = self {
+            if *arr.elem == self_ty {
+                Some(SelfKind::Array { len: &arr.len })
+            } else {
+                None
+            }
+        } else if let Type::Tuple(tpl) = self {
+            if self.contains_self()
+                && tpl
+                    .elems
+                    .iter()
+                    .all(|ty| !ty.contains_self() || ty.self_kind().is_some())
+            {
+                Some(SelfKind::Tuple(tpl))
+            } else {
+                None
+            }
+        } else if let Type::BareFn(f) = self {
+            let output = match &f.output {
+                ReturnType::Default => None,
+                ReturnType::Type(_, ty) => Some(&**ty),
+            };
+            if f.inputs
+                .iter()
+                .map(|arg| &arg.ty)
+                .chain(output)
+                .filter(|ty| ty.contains_self())
+                .all(|ty| {
+                    matches!(
+                        ty.self_kind(),
+                        Some(
+                            SelfKind::Ptr { .. }
+                                | SelfKind::Ref { .. }
+                                | SelfKind::Pin { .. }
+                                | SelfKind::Slice { .. }
+                        )
+                    )
+                })
+            {
+                Some(SelfKind::Fn(f))
+            } else {
+                None
+            }
+        } else if let Type::Path(path) = self
+            && let Some((ident, elem)) = single_argument(path)
+            && ["Box", "Rc", "Arc"].iter().any(|s| ident == s)
+        {
+            if *elem == self_ty {
+                Some(SelfKind::Smart {
+                    path,
+                    mutability: (ident == "Box").then(Default::default),
+                })
+            } else {
+                None
+            }
+        } else if let Type::Path(path) = self {
+            let Some(Type::Reference(TypeReference {
+                and_token,
+                lifetime,
+                mutability,
+                elem,
+            })) = pin_inner(path)
+            else {
+                return None;
+            };
+            if **elem == self_ty {
+                Some(SelfKind::Pin {
+                    and_token,
+                    lifetime,
+                    mutability,
+                })
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `T` if the path looks like `Pin<T>`.
+fn pin_inner(path: &TypePath) -> Option<&Type> {
+    single_argument(path)
+        .filter(|(ident, _)| *ident == "Pin")
+        .map(|(_, ty)| ty)
+}
+
+/// Returns `(Foo, T)` if the path looks like `Foo<T>`.
+fn single_argument(path: &TypePath) -> Option<(&Ident, &Type)> {
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    if args.args.len() != 1 {
+        return None;
+    }
+    match &args.args[0] {
+        GenericArgument::Type(ty) => Some((&segment.ident, ty)),
+        _ => None,
+    }
+}
+
+/// Returns the path of the smart pointer in `alloc`, declared as `__extern_trait_alloc`.
+///
+/// Smart pointers are recognized by name only, and the path written in the trait may name
+/// another type in the implementing crate. `::alloc` is not in scope without
+/// `extern crate alloc`, so the generated code declares the crate itself.
+fn smart_path(path: &TypePath) -> TokenStream {
+    let ident = &path.path.segments.last().unwrap().ident;
+    let module = match ident.to_string().as_str() {
+        "Box" => quote!(boxed),
+        "Rc" => quote!(rc),
+        _ => quote!(sync),
+    };
+    quote!(__extern_trait_alloc::#module::#ident)
+}
@@ -0,0 +1,44 @@
+/// Implemented by `#[extern_trait]` impls for the implementation type of a trait.
+///
+/// `KEY` is a hash of the symbol prefix of the trait, as the proxy type may be private to the
+/// declaring crate. The safe conversions of the proxy type, such as `wrap` and `into_inner`,
+/// only accept implementors, so a type merely implementing the trait cannot be reinterpreted
+/// as the linked implementation:
+///
+/// ```compile_fail
+/// use extern_trait::extern_trait;
+///
+/// #[extern_trait(NameProxy)]
+/// unsafe trait Name {
+///     fn len(&self) -> usize;
+/// }
+///
+/// struct NameImpl(&'static str);
+///
+/// #[extern_trait]
+/// unsafe impl Name for NameImpl {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+/// }
+///
+/// struct Other(u64, u64, u64);
+///
+/// unsafe impl Name for Other {
+///     fn len(&self) -> usize {
+///         0
+///     }
+/// }
+///
+/// NameProxy::wrap(Other(1, 2, 3));
+/// ```
+///
+/// # Safety
+///
+/// `Self` must be the type whose shims are linked for the trait, which only
+/// `#[extern_trait]` guarantees.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement the trait via `#[extern_trait]`",
+    label = "not the linked implementation type"
+)]
+pub unsafe trait ExternImplOf<const KEY: u64> {}
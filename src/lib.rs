@@ -1,21 +1,27 @@
 #![doc = include_str!("../README.md")]
+#![no_std]
 
-mod args;
-mod decl;
-mod imp;
-mod ty;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-use proc_macro::TokenStream;
-use syn::{Error, parse_macro_input};
+mod extern_impl;
+mod non_null_first;
+mod register_safe;
 
-#[proc_macro_attribute]
-pub fn extern_trait(args: TokenStream, input: TokenStream) -> TokenStream {
-    if !args.is_empty() {
-        let args = parse_macro_input!(args as args::Proxy);
-        decl::expand(args.into(), parse_macro_input!(input))
-    } else {
-        imp::expand(parse_macro_input!(input))
-    }
-    .unwrap_or_else(Error::into_compile_error)
-    .into()
-}
+/// Derives [`RegisterSafe`] for a struct, enum or union whose fields all implement it.
+///
+/// Crates re-exporting this crate can set the path to it with
+/// `#[register_safe(crate = path)]`.
+pub use extern_trait_macros::RegisterSafe;
+pub use register_safe::RegisterSafe;
+
+/// Derives [`NonNullFirst`] for a `#[repr(C)]` or `#[repr(transparent)]` struct whose first
+/// field implements it.
+///
+/// Crates re-exporting this crate can set the path to it with
+/// `#[non_null_first(crate = path)]`.
+pub use extern_trait_macros::NonNullFirst;
+pub use non_null_first::NonNullFirst;
+
+pub use extern_impl::ExternImplOf;
+pub use extern_trait_macros::extern_trait;
@@ -0,0 +1,73 @@
+use core::{mem::ManuallyDrop, num::NonZero, pin::Pin, ptr::NonNull};
+
+/// Types whose first word is never zero, e.g. because they start with a non-null pointer.
+///
+/// Implementors of an `#[extern_trait]` declared with [`niche = nonnull`](crate#niche)
+/// must implement it, so that the proxy type can store its first word as a [`NonNull`] and
+/// `Option<Proxy>` is as large as the proxy. Implement it with `#[derive(NonNullFirst)]` on a
+/// `#[repr(C)]` or `#[repr(transparent)]` struct whose first field implements it:
+///
+/// ```
+/// use extern_trait::NonNullFirst;
+///
+/// #[derive(NonNullFirst)]
+/// #[repr(C)]
+/// struct Node {
+///     name: &'static str,
+///     depth: usize,
+/// }
+/// ```
+///
+/// Integers smaller than a word are rejected, as the rest of the first word may be zero:
+///
+/// ```compile_fail
+/// use std::num::NonZero;
+///
+/// use extern_trait::NonNullFirst;
+///
+/// #[derive(NonNullFirst)]
+/// #[repr(C)]
+/// struct Id {
+///     id: NonZero<u32>,
+///     generation: u32,
+/// }
+/// ```
+///
+/// # Safety
+///
+/// The bytes of the first `usize` of the type must never be all zero.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not known to start with a non-null word",
+    note = "derive `NonNullFirst` for a `#[repr(C)]` struct starting with a reference, `NonNull`, `Box` and so on"
+)]
+pub unsafe trait NonNullFirst {}
+
+macro_rules! impl_non_null_first {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl NonNullFirst for $ty {})*
+    };
+}
+
+// Only integers filling exactly the first word, as smaller ones leave padding in it and the
+// first word of larger ones may be zero.
+impl_non_null_first!(NonZero<usize>, NonZero<isize>);
+
+#[cfg(target_pointer_width = "64")]
+impl_non_null_first!(NonZero<u64>, NonZero<i64>);
+
+unsafe impl<T: ?Sized> NonNullFirst for &T {}
+unsafe impl<T: ?Sized> NonNullFirst for &mut T {}
+unsafe impl<T: ?Sized> NonNullFirst for NonNull<T> {}
+unsafe impl<T: NonNullFirst> NonNullFirst for ManuallyDrop<T> {}
+unsafe impl<P: NonNullFirst> NonNullFirst for Pin<P> {}
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    use alloc::{boxed::Box, rc::Rc, sync::Arc};
+
+    use crate::NonNullFirst;
+
+    unsafe impl<T: ?Sized> NonNullFirst for Box<T> {}
+    unsafe impl<T: ?Sized> NonNullFirst for Rc<T> {}
+    unsafe impl<T: ?Sized> NonNullFirst for Arc<T> {}
+}
@@ -0,0 +1,94 @@
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::{PhantomData, PhantomPinned},
+    mem::ManuallyDrop,
+    num::{NonZero, Wrapping},
+    pin::Pin,
+    ptr::NonNull,
+    sync::atomic,
+};
+
+/// Types that only contain integer, pointer and reference data.
+///
+/// Such types never use floating point registers in calling conventions, which is one of the
+/// requirements for implementors of `#[extern_trait]`, and is checked with
+/// [`checked_abi`](crate#checked-abi). Implement it with `#[derive(RegisterSafe)]`, which
+/// requires every field to implement it, so types containing floating point numbers are
+/// rejected:
+///
+/// ```compile_fail
+/// use extern_trait::RegisterSafe;
+///
+/// #[derive(RegisterSafe)]
+/// struct Ratio(f64);
+/// ```
+///
+/// # Safety
+///
+/// The type must not contain floating point numbers or SIMD vectors, not even through
+/// generic parameters.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` may contain floating point data",
+    label = "not known to be passed in general purpose registers",
+    note = "derive `RegisterSafe` for types containing only integer, pointer and reference data"
+)]
+pub unsafe trait RegisterSafe {}
+
+macro_rules! impl_register_safe {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl RegisterSafe for $ty {})*
+    };
+}
+
+impl_register_safe! {
+    (), bool, char,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    NonZero<u8>, NonZero<u16>, NonZero<u32>, NonZero<u64>, NonZero<u128>, NonZero<usize>,
+    NonZero<i8>, NonZero<i16>, NonZero<i32>, NonZero<i64>, NonZero<i128>, NonZero<isize>,
+    atomic::AtomicBool,
+    atomic::AtomicU8, atomic::AtomicU16, atomic::AtomicU32, atomic::AtomicU64, atomic::AtomicUsize,
+    atomic::AtomicI8, atomic::AtomicI16, atomic::AtomicI32, atomic::AtomicI64, atomic::AtomicIsize,
+    PhantomPinned,
+}
+
+unsafe impl<T: ?Sized> RegisterSafe for *const T {}
+unsafe impl<T: ?Sized> RegisterSafe for *mut T {}
+unsafe impl<T: ?Sized> RegisterSafe for &T {}
+unsafe impl<T: ?Sized> RegisterSafe for &mut T {}
+unsafe impl<T: ?Sized> RegisterSafe for NonNull<T> {}
+unsafe impl<T> RegisterSafe for atomic::AtomicPtr<T> {}
+unsafe impl<T: ?Sized> RegisterSafe for PhantomData<T> {}
+
+unsafe impl<T: RegisterSafe> RegisterSafe for Option<T> {}
+unsafe impl<T: RegisterSafe, E: RegisterSafe> RegisterSafe for Result<T, E> {}
+unsafe impl<T: RegisterSafe, const N: usize> RegisterSafe for [T; N] {}
+unsafe impl<T: RegisterSafe> RegisterSafe for Wrapping<T> {}
+unsafe impl<T: RegisterSafe + ?Sized> RegisterSafe for Cell<T> {}
+unsafe impl<T: RegisterSafe + ?Sized> RegisterSafe for UnsafeCell<T> {}
+unsafe impl<T: RegisterSafe + ?Sized> RegisterSafe for ManuallyDrop<T> {}
+unsafe impl<P: RegisterSafe> RegisterSafe for Pin<P> {}
+
+macro_rules! impl_register_safe_tuple {
+    ($($name:ident)+) => {
+        unsafe impl<$($name: RegisterSafe),+> RegisterSafe for ($($name,)+) {}
+    };
+}
+
+impl_register_safe_tuple!(A);
+impl_register_safe_tuple!(A B);
+impl_register_safe_tuple!(A B C);
+impl_register_safe_tuple!(A B C D);
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    use alloc::{boxed::Box, rc, sync};
+
+    use crate::RegisterSafe;
+
+    unsafe impl<T: ?Sized> RegisterSafe for Box<T> {}
+    unsafe impl<T: ?Sized> RegisterSafe for rc::Rc<T> {}
+    unsafe impl<T: ?Sized> RegisterSafe for rc::Weak<T> {}
+    unsafe impl<T: ?Sized> RegisterSafe for sync::Arc<T> {}
+    unsafe impl<T: ?Sized> RegisterSafe for sync::Weak<T> {}
+}
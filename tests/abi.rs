@@ -0,0 +1,27 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(PairProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Pair {
+    fn new(tag: u8, value: u64) -> Self;
+    fn get(&self) -> (u8, u64);
+}
+
+struct PairImpl(u8, u64);
+
+#[extern_trait]
+unsafe impl Pair for PairImpl {
+    fn new(tag: u8, value: u64) -> Self {
+        Self(tag, value)
+    }
+
+    fn get(&self) -> (u8, u64) {
+        (self.0, self.1)
+    }
+}
+
+#[test]
+fn test_verify_abi() {
+    assert!(PairProxy::verify_abi());
+    assert_eq!(PairProxy::new(7, 42).get(), (7, 42));
+}
@@ -0,0 +1,58 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(CounterProxy, abi_version = "1")]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Counter {
+    fn new(start: u32) -> Self;
+    fn next(&mut self) -> u32;
+}
+
+#[extern_trait(GaugeProxy, abi_version = semver)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Gauge {
+    fn new(level: u32) -> Self;
+    fn level(&self) -> u32;
+}
+
+struct CounterImpl(u32);
+
+#[extern_trait]
+unsafe impl Counter for CounterImpl {
+    fn new(start: u32) -> Self {
+        Self(start)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+struct GaugeImpl(u32);
+
+#[extern_trait]
+unsafe impl Gauge for GaugeImpl {
+    fn new(level: u32) -> Self {
+        Self(level)
+    }
+
+    fn level(&self) -> u32 {
+        self.0
+    }
+}
+
+// Depends on the file declaring the trait, but not on the line, so it stays the same when
+// editing this file.
+unsafe extern "Rust" {
+    #[link_name = "__extern_trait_14extern_2dtrait7abi_3a112abi__version16ee240955cbce4eb17Counter3new16a46703bb56c6b491"]
+    safe fn new_counter(start: u32) -> CounterProxy;
+}
+
+#[test]
+fn test_abi_version() {
+    let mut counter = CounterProxy::new(1);
+    assert_eq!(counter.next(), 2);
+    assert_eq!(counter.next(), 3);
+    assert_eq!(new_counter(5).next(), 6);
+    assert_eq!(GaugeProxy::new(7).level(), 7);
+}
@@ -0,0 +1,74 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(CounterProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Counter {
+    fn new() -> Self;
+    /// Returns the current count.
+    fn get(&self) -> u32;
+    fn add(&mut self, n: u32);
+    fn count(&self) -> &u32;
+    fn count_mut(&mut self) -> &mut u32;
+}
+
+// The declaring side only sees the borrowed proxies.
+fn bump(mut counter: CounterProxyMut<'_>) -> u32 {
+    counter.add(2);
+    counter.get() + read(counter.as_proxy_ref())
+}
+
+fn read(counter: CounterProxyRef<'_>) -> u32 {
+    counter.get()
+}
+
+struct CounterImpl(u32);
+
+#[extern_trait]
+unsafe impl Counter for CounterImpl {
+    fn new() -> Self {
+        Self(0)
+    }
+
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    fn add(&mut self, n: u32) {
+        self.0 += n;
+    }
+
+    fn count(&self) -> &u32 {
+        &self.0
+    }
+
+    fn count_mut(&mut self) -> &mut u32 {
+        &mut self.0
+    }
+}
+
+struct State {
+    name: &'static str,
+    counter: CounterImpl,
+}
+
+#[test]
+fn test_borrowed() {
+    let mut state = State {
+        name: "state",
+        counter: CounterImpl::new(),
+    };
+    assert_eq!(bump(CounterProxyMut::wrap(&mut state.counter)), 4);
+    assert_eq!(read(CounterProxyRef::wrap(&state.counter)), 2);
+    assert_eq!(state.counter.0, 2);
+    *CounterProxyMut::wrap(&mut state.counter).count_mut() += 1;
+    assert_eq!(CounterProxyRef::wrap(&state.counter).count(), &3);
+    assert_eq!(state.name, "state");
+
+    let mut proxy = CounterProxy::new();
+    proxy.add(5);
+    assert_eq!(bump((&mut proxy).into()), 14);
+    assert_eq!(read((&proxy).into()), 7);
+    *proxy.count_mut() = 1;
+    assert_eq!(proxy.count(), &1);
+    assert_eq!(CounterProxyMut::from(&mut proxy).count(), &1);
+}
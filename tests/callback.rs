@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use extern_trait::extern_trait;
+
+#[extern_trait(ButtonProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Button {
+    fn new(id: usize) -> Self;
+    fn id(&self) -> usize;
+    fn set_callback(&mut self, cb: fn(&Self, usize));
+    fn click(&self, times: usize);
+}
+
+mod button_impl {
+    use super::*;
+
+    struct ButtonImpl {
+        id: usize,
+        cb: Option<fn(&Self, usize)>,
+    }
+
+    #[extern_trait]
+    unsafe impl Button for ButtonImpl {
+        fn new(id: usize) -> Self {
+            Self { id, cb: None }
+        }
+
+        fn id(&self) -> usize {
+            self.id
+        }
+
+        fn set_callback(&mut self, cb: fn(&Self, usize)) {
+            self.cb = Some(cb);
+        }
+
+        fn click(&self, times: usize) {
+            if let Some(cb) = self.cb {
+                cb(self, times);
+            }
+        }
+    }
+}
+
+static CLICKS: AtomicUsize = AtomicUsize::new(0);
+
+fn on_click(button: &ButtonProxy, times: usize) {
+    CLICKS.fetch_add(button.id() * times, Ordering::Relaxed);
+}
+
+#[test]
+fn test_callback() {
+    let mut button = ButtonProxy::new(10);
+    button.click(1);
+    assert_eq!(CLICKS.load(Ordering::Relaxed), 0);
+
+    button.set_callback(on_click);
+    button.click(2);
+    assert_eq!(CLICKS.load(Ordering::Relaxed), 20);
+}
@@ -0,0 +1,41 @@
+use std::num::NonZero;
+
+use extern_trait::{RegisterSafe, extern_trait};
+
+#[extern_trait(CounterProxy, checked_abi)]
+trait Counter {
+    fn new(step: usize) -> Self;
+    fn next(&mut self) -> usize;
+}
+
+#[derive(RegisterSafe)]
+struct Wrapper<T>(T);
+
+#[derive(RegisterSafe)]
+struct CounterImpl {
+    count: Wrapper<usize>,
+    step: Option<NonZero<usize>>,
+}
+
+#[extern_trait]
+impl Counter for CounterImpl {
+    fn new(step: usize) -> Self {
+        Self {
+            count: Wrapper(0),
+            step: NonZero::new(step),
+        }
+    }
+
+    fn next(&mut self) -> usize {
+        self.count.0 += self.step.map_or(0, NonZero::get);
+        self.count.0
+    }
+}
+
+#[test]
+fn test_checked_abi() {
+    let mut counter = CounterProxy::new(3);
+    assert_eq!(counter.next(), 3);
+    assert_eq!(counter.next(), 6);
+    assert_eq!(CounterProxy::new(0).next(), 0);
+}
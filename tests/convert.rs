@@ -0,0 +1,32 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(NameProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Name {
+    fn len(&self) -> usize;
+}
+
+struct NameImpl(&'static str);
+
+#[extern_trait]
+unsafe impl Name for NameImpl {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[test]
+fn test_convert() {
+    let mut name = NameProxy::wrap(NameImpl("ferris"));
+    assert_eq!(name.len(), 6);
+    assert_eq!(name.as_inner::<NameImpl>().0, "ferris");
+
+    name.as_inner_mut::<NameImpl>().0 = "crab";
+    assert_eq!(name.len(), 4);
+
+    let name = name.into_inner::<NameImpl>();
+    assert_eq!(name.0, "crab");
+
+    let name = NameProxy::from(NameImpl("ferris"));
+    assert_eq!(name.len(), 6);
+}
@@ -0,0 +1,26 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(ClockProxy, crate_id = "//platform/time:clock")]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Clock {
+    fn new(ticks: u64) -> Self;
+    fn now(&self) -> u64;
+}
+
+struct ClockImpl(u64);
+
+#[extern_trait]
+unsafe impl Clock for ClockImpl {
+    fn new(ticks: u64) -> Self {
+        Self(ticks)
+    }
+
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+fn test_crate_id() {
+    assert_eq!(ClockProxy::new(42).now(), 42);
+}
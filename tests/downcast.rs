@@ -0,0 +1,56 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(ShapeProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Shape {
+    fn new(side: u32) -> Self;
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+#[extern_trait]
+unsafe impl Shape for Square {
+    fn new(side: u32) -> Self {
+        Self(side)
+    }
+
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+struct Circle;
+
+unsafe impl Shape for Circle {
+    fn new(_: u32) -> Self {
+        Circle
+    }
+
+    fn area(&self) -> u32 {
+        0
+    }
+}
+
+#[test]
+fn test_downcast() {
+    assert!(ShapeProxy::is::<Square>());
+    assert!(!ShapeProxy::is::<Circle>());
+    assert!(ShapeProxy::implementor_type_name().ends_with("Square"));
+
+    let mut shape = ShapeProxy::new(3);
+    assert!(shape.try_downcast_ref::<Circle>().is_none());
+    assert_eq!(shape.try_downcast_ref::<Square>().unwrap().0, 3);
+
+    assert!(shape.try_downcast_mut::<Circle>().is_none());
+    shape.try_downcast_mut::<Square>().unwrap().0 = 4;
+    assert_eq!(shape.area(), 16);
+
+    let Err(shape) = shape.try_into_impl::<Circle>() else {
+        panic!("`Circle` is not the implementation");
+    };
+    let Ok(square) = shape.try_into_impl::<Square>() else {
+        panic!("`Square` is the implementation");
+    };
+    assert_eq!(square.0, 4);
+}
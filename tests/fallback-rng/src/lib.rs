@@ -0,0 +1,47 @@
+//! Declarations for `tests/fallback.rs`, since the fallback is selected by a feature of
+//! the declaring crate.
+//!
+//! While the `fallback` feature is enabled, other implementations are rejected:
+//!
+//! ```compile_fail
+//! use extern_trait::extern_trait;
+//! use fallback_rng::Rng;
+//!
+//! struct CountingRng(u64);
+//!
+//! #[extern_trait]
+//! unsafe impl Rng for CountingRng {
+//!     fn new(seed: u64) -> Self {
+//!         Self(seed)
+//!     }
+//!
+//!     fn next(&mut self) -> u64 {
+//!         self.0 += 1;
+//!         self.0
+//!     }
+//! }
+//! ```
+
+use extern_trait::extern_trait;
+
+#[extern_trait(pub RngProxy, fallback = SoftRng)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Rng {
+    fn new(seed: u64) -> Self;
+    fn next(&mut self) -> u64;
+}
+
+pub struct SoftRng(u64);
+
+unsafe impl Rng for SoftRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
@@ -0,0 +1,15 @@
+use fallback_rng::{Rng, RngProxy, SoftRng};
+
+#[test]
+fn test_fallback() {
+    let mut proxy = RngProxy::new(1);
+    let mut soft = SoftRng::new(1);
+    for _ in 0..4 {
+        assert_eq!(proxy.next(), soft.next());
+    }
+
+    let mut proxy = RngProxy::wrap(SoftRng::new(2));
+    let mut soft = SoftRng::new(2);
+    assert_eq!(proxy.next(), soft.next());
+    assert_eq!(proxy.into_inner::<SoftRng>().next(), soft.next());
+}
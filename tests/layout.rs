@@ -0,0 +1,31 @@
+use std::alloc::Layout;
+
+use extern_trait::extern_trait;
+
+#[extern_trait(BufferProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Buffer {
+    fn new(len: usize) -> Self;
+    fn len(&self) -> usize;
+}
+
+struct BufferImpl(Box<[u8]>);
+
+#[extern_trait]
+unsafe impl Buffer for BufferImpl {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len].into_boxed_slice())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[test]
+fn test_layout() {
+    assert_eq!(BufferProxy::layout(), Layout::new::<BufferImpl>());
+    assert!(BufferProxy::needs_drop());
+    assert_eq!(BufferProxy::self_check(), Ok(()));
+    assert_eq!(BufferProxy::new(5).len(), 5);
+}
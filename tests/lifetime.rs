@@ -0,0 +1,54 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(CursorProxy<'a>)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self;
+    fn next(&mut self) -> Option<&'a u8>;
+    fn rest(&self) -> &'a [u8];
+}
+
+struct SliceCursor<'a> {
+    rest: &'a [u8],
+}
+
+#[extern_trait]
+unsafe impl<'a> Cursor<'a> for SliceCursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { rest: data }
+    }
+
+    fn next(&mut self) -> Option<&'a u8> {
+        let (byte, rest) = self.rest.split_first()?;
+        self.rest = rest;
+        Some(byte)
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        self.rest
+    }
+}
+
+fn first<'a>(cursor: &mut CursorProxy<'a>) -> Option<&'a u8> {
+    cursor.next()
+}
+
+#[test]
+fn test_lifetime() {
+    let data = vec![1, 2, 3];
+    let mut cursor = CursorProxy::new(&data);
+    let byte = first(&mut cursor);
+    assert_eq!(byte, Some(&1));
+    assert_eq!(cursor.rest(), &[2, 3]);
+    drop(cursor);
+    assert_eq!(byte, Some(&1));
+
+    let cursor = CursorProxy::from(SliceCursor::new(&data));
+    let inner = cursor.into_inner::<SliceCursor>();
+    assert_eq!(inner.rest(), &[1, 2, 3]);
+
+    assert_eq!(
+        CursorProxy::implementor_type_name(),
+        std::any::type_name::<SliceCursor<'static>>()
+    );
+}
@@ -0,0 +1,56 @@
+mod disk {
+    use extern_trait::extern_trait;
+
+    #[extern_trait(pub DeviceProxy)]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe trait Device {
+        fn new() -> Self;
+        fn name(&self) -> &'static str;
+    }
+
+    struct Disk;
+
+    #[extern_trait]
+    unsafe impl Device for Disk {
+        fn new() -> Self {
+            Disk
+        }
+
+        fn name(&self) -> &'static str {
+            "disk"
+        }
+    }
+}
+
+mod net {
+    use extern_trait::extern_trait;
+
+    #[extern_trait(pub DeviceProxy, namespace = "net")]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe trait Device {
+        fn new() -> Self;
+        fn name(&self) -> &'static str;
+    }
+
+    struct Nic;
+
+    #[extern_trait]
+    unsafe impl Device for Nic {
+        fn new() -> Self {
+            Nic
+        }
+
+        fn name(&self) -> &'static str {
+            "nic"
+        }
+    }
+}
+
+#[test]
+fn test_namespace() {
+    use disk::Device as _;
+    use net::Device as _;
+
+    assert_eq!(disk::DeviceProxy::new().name(), "disk");
+    assert_eq!(net::DeviceProxy::new().name(), "nic");
+}
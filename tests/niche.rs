@@ -0,0 +1,50 @@
+use std::{mem::size_of, sync::Arc};
+
+use extern_trait::{NonNullFirst, extern_trait};
+
+#[extern_trait(NodeProxy, niche = nonnull)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Node {
+    fn new(name: &'static str, depth: usize) -> Self;
+    fn depth(&self) -> usize;
+}
+
+#[derive(NonNullFirst)]
+#[repr(C)]
+struct NodeImpl {
+    name: Arc<String>,
+    depth: usize,
+}
+
+#[extern_trait]
+unsafe impl Node for NodeImpl {
+    fn new(name: &'static str, depth: usize) -> Self {
+        Self {
+            name: Arc::new(name.to_string()),
+            depth: depth + name.len(),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+fn find(depth: usize) -> Option<NodeProxy> {
+    (depth > 0).then(|| NodeProxy::new("node", depth))
+}
+
+#[test]
+fn test_niche() {
+    assert_eq!(size_of::<Option<NodeProxy>>(), size_of::<NodeProxy>());
+
+    assert!(find(0).is_none());
+    let node = find(1).unwrap();
+    assert_eq!(node.depth(), 5);
+    assert_eq!(node.as_inner::<NodeImpl>().name.as_str(), "node");
+
+    let parts = node.into_raw_parts();
+    assert!(!parts[0].is_null());
+    let node = unsafe { NodeProxy::from_raw_parts(parts) };
+    assert_eq!(node.depth(), 5);
+}
@@ -0,0 +1,103 @@
+use std::{
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+};
+
+use extern_trait::extern_trait;
+
+#[extern_trait(CountdownProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Countdown {
+    fn new(n: u32) -> Self;
+    fn remaining(self: Pin<&Self>) -> u32;
+    fn poll_done(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()>;
+}
+
+// Pinned only within a tuple, which still makes the proxy `!Unpin`.
+#[extern_trait(TickerProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Ticker {
+    fn leak(n: u32) -> (Pin<&'static mut Self>, u32);
+    fn count(&self) -> u32;
+}
+
+// Only resolves if `T` is `!Unpin`, as both impls apply otherwise.
+trait AmbiguousIfUnpin<A> {
+    fn check() {}
+}
+
+impl<T: ?Sized> AmbiguousIfUnpin<()> for T {}
+impl<T: ?Sized + Unpin> AmbiguousIfUnpin<u8> for T {}
+
+const _: fn() = <CountdownProxy as AmbiguousIfUnpin<_>>::check;
+const _: fn() = <TickerProxy as AmbiguousIfUnpin<_>>::check;
+
+mod countdown_impl {
+    use std::marker::PhantomPinned;
+
+    use super::*;
+
+    struct CountdownImpl(u32, PhantomPinned);
+
+    #[extern_trait]
+    unsafe impl Countdown for CountdownImpl {
+        fn new(n: u32) -> Self {
+            Self(n, PhantomPinned)
+        }
+
+        fn remaining(self: Pin<&Self>) -> u32 {
+            self.0
+        }
+
+        fn poll_done(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let this = unsafe { self.get_unchecked_mut() };
+            if this.0 == 0 {
+                Poll::Ready(())
+            } else {
+                this.0 -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+mod ticker_impl {
+    use std::marker::PhantomPinned;
+
+    use super::*;
+
+    struct TickerImpl(u32, PhantomPinned);
+
+    #[extern_trait]
+    unsafe impl Ticker for TickerImpl {
+        fn leak(n: u32) -> (Pin<&'static mut Self>, u32) {
+            let this = Box::leak(Box::new(Self(n, PhantomPinned)));
+            (unsafe { Pin::new_unchecked(this) }, n)
+        }
+
+        fn count(&self) -> u32 {
+            self.0
+        }
+    }
+}
+
+#[test]
+fn test_pin() {
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut countdown = pin!(CountdownProxy::new(2));
+
+    assert_eq!(countdown.as_ref().remaining(), 2);
+    assert_eq!(countdown.as_mut().poll_done(&mut cx), Poll::Pending);
+    assert_eq!(countdown.as_ref().remaining(), 1);
+    assert_eq!(countdown.as_mut().poll_done(&mut cx), Poll::Pending);
+    assert_eq!(countdown.as_mut().poll_done(&mut cx), Poll::Ready(()));
+    assert_eq!(countdown.as_ref().remaining(), 0);
+}
+
+#[test]
+fn test_pin_in_tuple() {
+    let (ticker, n) = TickerProxy::leak(1);
+    assert_eq!(n, 1);
+    assert_eq!(ticker.count(), 1);
+}
@@ -0,0 +1,54 @@
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use extern_trait::extern_trait;
+
+#[extern_trait(TaskProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Task {
+    fn new(id: usize) -> Self;
+    fn id(&self) -> usize;
+}
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct TaskImpl(usize);
+
+impl Drop for TaskImpl {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[extern_trait]
+unsafe impl Task for TaskImpl {
+    fn new(id: usize) -> Self {
+        Self(id)
+    }
+
+    fn id(&self) -> usize {
+        self.0
+    }
+}
+
+// Stands in for a C callback receiving the proxy as two opaque pointers.
+extern "C" fn callback(data: *mut c_void, vtable: *mut c_void) -> usize {
+    let task = unsafe {
+        TaskProxy::from_raw_parts([data.cast_const().cast(), vtable.cast_const().cast()])
+    };
+    task.id()
+}
+
+#[test]
+fn test_raw_parts() {
+    let [data, vtable] = TaskProxy::new(7).into_raw_parts();
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 0);
+
+    assert_eq!(
+        callback(data.cast_mut().cast(), vtable.cast_mut().cast()),
+        7
+    );
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+}
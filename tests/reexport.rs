@@ -0,0 +1,31 @@
+mod runtime {
+    pub use extern_trait::*;
+}
+
+use runtime::{RegisterSafe, extern_trait};
+
+#[extern_trait(SlotProxy, checked_abi, crate = crate::runtime)]
+trait Slot {
+    fn new(value: usize) -> Self;
+    fn get(&self) -> usize;
+}
+
+#[derive(RegisterSafe)]
+#[register_safe(crate = crate::runtime)]
+struct SlotImpl(usize);
+
+#[extern_trait]
+impl Slot for SlotImpl {
+    fn new(value: usize) -> Self {
+        Self(value)
+    }
+
+    fn get(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn test_reexport() {
+    assert_eq!(SlotProxy::new(42).get(), 42);
+}
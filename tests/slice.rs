@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use extern_trait::extern_trait;
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[extern_trait(CellProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Cell: Sized {
+    fn new(v: usize) -> Self;
+    fn pair(a: usize, b: usize) -> [Self; 2];
+    fn merge(cells: [Self; 2]) -> Self;
+    fn sum(batch: &[Self]) -> usize;
+    fn bump_all(batch: &mut [Self]);
+    fn get(&self) -> usize;
+}
+
+mod cell_impl {
+    use super::*;
+
+    struct CellImpl(usize, usize);
+
+    impl Drop for CellImpl {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[extern_trait]
+    unsafe impl Cell for CellImpl {
+        fn new(v: usize) -> Self {
+            Self(v, 0)
+        }
+
+        fn pair(a: usize, b: usize) -> [Self; 2] {
+            [Self::new(a), Self::new(b)]
+        }
+
+        fn merge(cells: [Self; 2]) -> Self {
+            let [a, b] = cells;
+            Self(a.0 + b.0, a.1 + b.1 + 1)
+        }
+
+        fn sum(batch: &[Self]) -> usize {
+            batch.iter().map(|c| c.0).sum()
+        }
+
+        fn bump_all(batch: &mut [Self]) {
+            for c in batch {
+                c.0 += 1;
+            }
+        }
+
+        fn get(&self) -> usize {
+            self.0
+        }
+    }
+}
+
+#[test]
+fn test_slice() {
+    let [a, b] = CellProxy::pair(1, 2);
+    let mut cells = vec![a, b, CellProxy::new(3)];
+    assert_eq!(CellProxy::sum(&cells), 6);
+
+    CellProxy::bump_all(&mut cells);
+    assert_eq!(CellProxy::sum(&cells), 9);
+    assert_eq!(cells[2].get(), 4);
+
+    let c = cells.pop().unwrap();
+    let d = cells.pop().unwrap();
+    let merged = CellProxy::merge([c, d]);
+    assert_eq!(merged.get(), 7);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+
+    drop(merged);
+    drop(cells);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 4);
+}
@@ -0,0 +1,94 @@
+use std::{rc::Rc, sync::Arc};
+
+use extern_trait::extern_trait;
+
+#[extern_trait(TaskProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Task {
+    fn boxed(id: usize) -> Box<Self>;
+    fn shared(id: usize) -> Arc<Self>;
+    fn id(&self) -> usize;
+    fn runs(&self) -> usize;
+    fn run(self: Arc<Self>) -> usize;
+    fn run_local(self: Rc<Self>) -> usize;
+    fn finish(self: Box<Self>) -> usize;
+}
+
+mod task_impl {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    // The generated code names the smart pointers in `alloc` rather than the ones in scope.
+    #[allow(dead_code)]
+    struct Box;
+
+    struct TaskImpl {
+        id: usize,
+        runs: AtomicUsize,
+    }
+
+    #[extern_trait]
+    unsafe impl Task for TaskImpl {
+        fn boxed(id: usize) -> std::boxed::Box<Self> {
+            std::boxed::Box::new(Self {
+                id,
+                runs: AtomicUsize::new(0),
+            })
+        }
+
+        fn shared(id: usize) -> Arc<Self> {
+            Arc::new(Self {
+                id,
+                runs: AtomicUsize::new(0),
+            })
+        }
+
+        fn id(&self) -> usize {
+            self.id
+        }
+
+        fn runs(&self) -> usize {
+            self.runs.load(Ordering::Relaxed)
+        }
+
+        fn run(self: Arc<Self>) -> usize {
+            self.runs.fetch_add(1, Ordering::Relaxed);
+            Arc::strong_count(&self)
+        }
+
+        fn run_local(self: Rc<Self>) -> usize {
+            self.runs.fetch_add(1, Ordering::Relaxed);
+            Rc::strong_count(&self)
+        }
+
+        fn finish(self: std::boxed::Box<Self>) -> usize {
+            self.runs.load(Ordering::Relaxed)
+        }
+    }
+}
+
+#[test]
+fn test_arc() {
+    let task = TaskProxy::shared(1);
+    assert_eq!(task.id(), 1);
+    assert_eq!(task.clone().run(), 2);
+    assert_eq!(task.clone().run(), 2);
+    assert_eq!(task.runs(), 2);
+    assert_eq!(Arc::strong_count(&task), 1);
+}
+
+#[test]
+fn test_rc() {
+    let task = Rc::new(*TaskProxy::boxed(3));
+    assert_eq!(task.clone().run_local(), 2);
+    assert_eq!(task.runs(), 1);
+    assert_eq!(Rc::strong_count(&task), 1);
+}
+
+#[test]
+fn test_box() {
+    let task = TaskProxy::boxed(2);
+    assert_eq!(task.id(), 2);
+    assert_eq!(task.finish(), 0);
+}
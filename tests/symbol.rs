@@ -0,0 +1,50 @@
+use extern_trait::extern_trait;
+use renamed::{Gauge, GaugeProxy};
+
+#[extern_trait(SensorProxy, symbol = "sensor_v1")]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Sensor {
+    fn new(value: i32) -> Self;
+    #[extern_trait(symbol = "sensor_v1_read")]
+    fn value(&self) -> i32;
+}
+
+struct SensorImpl(i32);
+
+#[extern_trait]
+unsafe impl Sensor for SensorImpl {
+    fn new(value: i32) -> Self {
+        Self(value)
+    }
+
+    fn value(&self) -> i32 {
+        self.0
+    }
+}
+
+// A later version of the trait, renamed and moved to another module, which keeps the
+// symbols and still links against `SensorImpl`.
+mod renamed {
+    use extern_trait::extern_trait;
+
+    #[extern_trait(pub GaugeProxy, symbol = "sensor_v1")]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe trait Gauge {
+        fn new(value: i32) -> Self;
+        #[extern_trait(symbol = "sensor_v1_read")]
+        fn read(&self) -> i32;
+    }
+}
+
+unsafe extern "Rust" {
+    #[link_name = "sensor_v1_read"]
+    safe fn read(sensor: &SensorProxy) -> i32;
+}
+
+#[test]
+fn test_symbol() {
+    let sensor = SensorProxy::new(-5);
+    assert_eq!(sensor.value(), -5);
+    assert_eq!(read(&sensor), -5);
+    assert_eq!(GaugeProxy::new(3).read(), 3);
+}
@@ -0,0 +1,58 @@
+use extern_trait::extern_trait;
+
+#[extern_trait(SpanProxy)]
+#[allow(clippy::missing_safety_doc)]
+unsafe trait Span: Sized {
+    fn with_len(len: usize) -> (Self, usize);
+    fn split(&self) -> (Self, Self);
+    fn nested(&self) -> ((Self, u8), [Self; 2], Box<Self>);
+    fn bounds(&self) -> (usize, usize);
+}
+
+mod span_impl {
+    use super::*;
+
+    struct SpanImpl(usize, usize);
+
+    #[extern_trait]
+    unsafe impl Span for SpanImpl {
+        fn with_len(len: usize) -> (Self, usize) {
+            (Self(0, len), len)
+        }
+
+        fn split(&self) -> (Self, Self) {
+            let mid = (self.0 + self.1) / 2;
+            (Self(self.0, mid), Self(mid, self.1))
+        }
+
+        fn nested(&self) -> ((Self, u8), [Self; 2], Box<Self>) {
+            (
+                (Self(self.0, self.1), 7),
+                [Self(self.0, self.0), Self(self.1, self.1)],
+                Box::new(Self(self.1, self.0)),
+            )
+        }
+
+        fn bounds(&self) -> (usize, usize) {
+            (self.0, self.1)
+        }
+    }
+}
+
+#[test]
+fn test_tuple_self() {
+    let (span, len) = SpanProxy::with_len(10);
+    assert_eq!(len, 10);
+    assert_eq!(span.bounds(), (0, 10));
+
+    let (left, right) = span.split();
+    assert_eq!(left.bounds(), (0, 5));
+    assert_eq!(right.bounds(), (5, 10));
+
+    let ((same, tag), [start, end], swapped) = right.nested();
+    assert_eq!(same.bounds(), (5, 10));
+    assert_eq!(tag, 7);
+    assert_eq!(start.bounds(), (5, 5));
+    assert_eq!(end.bounds(), (10, 10));
+    assert_eq!(swapped.bounds(), (10, 5));
+}
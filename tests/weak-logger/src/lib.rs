@@ -0,0 +1,27 @@
+//! Declarations for `tests/weak.rs`, which must live in another crate than their
+//! implementations to be weakly linked.
+
+#![cfg(feature = "nightly")]
+#![feature(linkage)]
+
+use extern_trait::extern_trait;
+
+#[extern_trait(pub LoggerProxy, weak)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Logger {
+    fn new(level: u8) -> Self;
+    fn level(&self) -> u8;
+}
+
+#[extern_trait(pub HookProxy, weak)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Hook {
+    fn new() -> Self;
+}
+
+#[extern_trait(pub PrefixProxy<'a>, weak)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Prefix<'a> {
+    fn new(prefix: &'a str) -> Self;
+    fn get(&self) -> &'a str;
+}
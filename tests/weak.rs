@@ -0,0 +1,46 @@
+use extern_trait::extern_trait;
+use weak_logger::{Hook, HookProxy, Logger, LoggerProxy, LoggerProxyRef, Prefix, PrefixProxy};
+
+struct LoggerImpl(u8);
+
+#[extern_trait]
+unsafe impl Logger for LoggerImpl {
+    fn new(level: u8) -> Self {
+        Self(level)
+    }
+
+    fn level(&self) -> u8 {
+        self.0
+    }
+}
+
+struct PrefixImpl<'a>(&'a str);
+
+#[extern_trait]
+unsafe impl<'a> Prefix<'a> for PrefixImpl<'a> {
+    fn new(prefix: &'a str) -> Self {
+        Self(prefix)
+    }
+
+    fn get(&self) -> &'a str {
+        self.0
+    }
+}
+
+#[test]
+fn test_implemented() {
+    assert!(LoggerProxy::is_implemented());
+    assert_eq!(LoggerProxy::new(3).level(), 3);
+    assert_eq!(LoggerProxyRef::wrap(&LoggerImpl(4)).level(), 4);
+
+    let name = String::from("net");
+    let prefix = PrefixProxy::new(&name);
+    assert_eq!(prefix.get(), "net");
+}
+
+#[test]
+#[should_panic = "no implementation is linked for `HookProxy::new`"]
+fn test_not_implemented() {
+    assert!(!HookProxy::is_implemented());
+    HookProxy::new();
+}